@@ -0,0 +1,476 @@
+// Copyright 2016 Revolution Solid & Contributors.
+// author(s): sysnett
+// rust-monster is licensed under a MIT License.
+
+//! Age-Layered Population Structure (ALPS)
+//!
+//! The population is split into layers by age. Individuals only compete
+//! with individuals of similar age, and move up a layer when they grow
+//! older than their layer's age limit. Fresh random individuals keep
+//! entering the bottom layer, so old converged solutions can't crowd out
+//! newcomers.
+//!
+//! Individuals must implement `GAIndividual::age` and `GAIndividual::set_age`.
+use ::ga::ga_core::{GAFactory, GAFlags, GeneticAlgorithm, GAIndividual};
+use ::ga::ga_population::{GAPopulation, GAPopulationSortBasis, GAPopulationSortOrder};
+use ::ga::ga_random::{GARandomCtx, GASeed};
+use ::ga::ga_selectors::*;
+use ::ga::ga_statistics::GAStatistics;
+
+use std::any::Any;
+use std::cmp;
+use std::mem;
+
+/// ALPS Evaluation Context
+struct AlpsEvaluationCtx;
+
+/// ALPS Genetic Algorithm Config
+#[derive(Copy, Clone, Default)]
+pub struct AlpsGACfg
+{
+    pub d_seed : GASeed,
+
+    pub max_generations         : i32,
+
+    pub num_layers              : usize,
+    pub layer_size              : usize,
+    // Layer i (but the top one) holds individuals up to `age_gap*(i+1)`
+    // generations old. The top layer has no age limit.
+    pub age_gap                 : u32,
+    // Random individuals injected into the bottom layer each generation.
+    pub num_immigrants          : usize,
+
+    pub probability_crossover   : f32,
+    pub probability_mutation    : f32,
+
+    pub population_sort_order : GAPopulationSortOrder,
+
+    pub flags                   : GAFlags,
+}
+
+impl AlpsGACfg
+{
+    /// Age limit of `layer`. `None` for the top layer.
+    pub fn age_limit(&self, layer: usize) -> Option<u32>
+    {
+        if layer + 1 >= self.num_layers
+        {
+            None
+        }
+        else
+        {
+            Some(self.age_gap * (layer as u32 + 1))
+        }
+    }
+}
+
+/// ALPS Genetic Algorithm
+///
+/// Each generation:
+///   * Every individual ages a generation.
+///   * Each layer breeds offspring out of itself and the layer below; the
+///     offspring are as old as their oldest parent. The best `layer_size` of
+///     the layer and its offspring survive.
+///   * Individuals older than their layer's age limit move up a layer.
+///   * `num_immigrants` random individuals replace the worst of the bottom
+///     layer.
+pub struct AlpsGA<'a, T: GAIndividual>
+{
+    current_generation : i32,
+    config : AlpsGACfg,
+    layers : Vec<GAPopulation<T>>,
+    factory : &'a mut GAFactory<T>,
+    rng_ctx : GARandomCtx,
+    eval_ctx : Option<&'a mut Any>,
+    statistics : GAStatistics<T>,
+}
+impl<'a, T: GAIndividual + Clone> AlpsGA<'a, T>
+{
+    pub fn new(cfg: AlpsGACfg, factory: &'a mut GAFactory<T>) -> AlpsGA<'a, T>
+    {
+        AlpsGA::new_with_eval_ctx(cfg, factory, None)
+    }
+
+    pub fn new_with_eval_ctx(cfg: AlpsGACfg,
+                             factory: &'a mut GAFactory<T>,
+                             eval_ctx: Option<&'a mut Any>) -> AlpsGA<'a, T>
+    {
+        assert!(cfg.num_layers > 0);
+
+        let rng = GARandomCtx::from_seed(cfg.d_seed, String::from("AlpsGA"));
+        let layers = (0..cfg.num_layers).map(|_| GAPopulation::new(vec![], cfg.population_sort_order)).collect();
+
+        AlpsGA { current_generation: 0, config: cfg, layers: layers, factory: factory, rng_ctx: rng, eval_ctx: eval_ctx, statistics: GAStatistics::new() }
+    }
+
+    /// Layers, from the youngest (bottom) to the oldest (top).
+    pub fn layers(&mut self) -> &mut [GAPopulation<T>]
+    {
+        &mut self.layers
+    }
+
+    fn evaluate(&mut self, inds: &mut [T])
+    {
+        for ind in inds.iter_mut()
+        {
+            match self.eval_ctx
+            {
+                Some(ref mut eval_ctx) => ind.evaluate(*eval_ctx),
+                None => ind.evaluate(&mut AlpsEvaluationCtx{} as &mut Any)
+            }
+        }
+    }
+
+    // New random individuals, age 0 and evaluated.
+    fn immigrants(&mut self, n: usize) -> Vec<T>
+    {
+        let mut inds = mem::take(self.factory.random_population(n, self.config.population_sort_order, &mut self.rng_ctx).population());
+        for ind in inds.iter_mut()
+        {
+            ind.set_age(0);
+        }
+        self.evaluate(&mut inds);
+        inds
+    }
+
+    // The best `k` of `inds` by raw score. Layers are never scaled, so their
+    // fitness scores mean nothing.
+    fn keep_best(&self, inds: Vec<T>, k: usize) -> Vec<T>
+    {
+        let mut pop = GAPopulation::new(inds, self.config.population_sort_order);
+        if pop.size() <= k
+        {
+            return mem::take(pop.population());
+        }
+        pop.sort();
+        pop.drain_best_individuals(k, GAPopulationSortBasis::Raw)
+    }
+
+    // Keep the best `layer_size` of `inds` as layer `i`.
+    fn set_layer(&mut self, i: usize, inds: Vec<T>)
+    {
+        let inds = self.keep_best(inds, self.config.layer_size);
+        let mut layer = GAPopulation::new(inds, self.config.population_sort_order);
+        layer.sort();
+        self.layers[i] = layer;
+    }
+
+    fn breed(&mut self, i: usize) -> Vec<T>
+    {
+        let mut parents = self.layers[i].population().clone();
+        if i > 0
+        {
+            parents.extend(self.layers[i-1].population().iter().cloned());
+        }
+        if parents.is_empty()
+        {
+            return vec![];
+        }
+
+        let mut pool = GAPopulation::new(parents, self.config.population_sort_order);
+        let mut offspring = pool.breed::<GARawScoreSelection>(self.config.layer_size,
+                                                               self.config.probability_crossover,
+                                                               self.config.probability_mutation,
+                                                               &mut self.rng_ctx);
+        self.evaluate(&mut offspring);
+        offspring
+    }
+}
+impl<'a, T: GAIndividual + Clone + PartialEq> GeneticAlgorithm<T> for AlpsGA<'a, T>
+{
+    /// The top (oldest) layer.
+    fn population(&mut self) -> &mut GAPopulation<T>
+    {
+        self.layers.last_mut().unwrap()
+    }
+
+    /// Statistics of the top layer, from the first generation it has any
+    /// individuals.
+    fn statistics(&self) -> &GAStatistics<T>
+    {
+        &self.statistics
+    }
+
+    /// Best individual of all the layers, by raw score: the top layer stays
+    /// empty until the first promotion. Sorts the layers if needed.
+    ///
+    /// Panics if all the layers are empty.
+    fn best(&mut self) -> &T
+    {
+        let order = self.config.population_sort_order;
+        let mut best : Option<&T> = None;
+        for layer in self.layers.iter_mut().filter(|layer| layer.size() > 0)
+        {
+            layer.sort();
+            let candidate = layer.best_by_raw_score();
+            let is_better = match best
+            {
+                Some(b) => match order
+                {
+                    GAPopulationSortOrder::HighIsBest => candidate.raw() > b.raw(),
+                    GAPopulationSortOrder::LowIsBest => candidate.raw() < b.raw()
+                },
+                None => true
+            };
+            if is_better
+            {
+                best = Some(candidate);
+            }
+        }
+        best.expect("AlpsGA - no individuals in any layer")
+    }
+
+    fn initialize_internal(&mut self)
+    {
+        let layer_size = self.config.layer_size;
+        let bottom = self.immigrants(layer_size);
+        self.set_layer(0, bottom);
+    }
+
+    fn step_internal(&mut self) -> i32
+    {
+        let num_layers = self.layers.len();
+
+        // Age
+        for layer in self.layers.iter_mut()
+        {
+            for ind in layer.population().iter_mut()
+            {
+                let age = ind.age();
+                ind.set_age(age + 1);
+            }
+        }
+
+        // Breed, top to bottom, so each layer breeds with the previous
+        // generation of the layer below.
+        for i in (0..num_layers).rev()
+        {
+            let offspring = self.breed(i);
+            let mut inds = mem::take(self.layers[i].population());
+            inds.extend(offspring);
+            self.set_layer(i, inds);
+        }
+
+        // Promote, bottom to top.
+        for i in 0..num_layers
+        {
+            let limit = match self.config.age_limit(i)
+            {
+                Some(limit) => limit,
+                None => break
+            };
+
+            let inds = mem::take(self.layers[i].population());
+            let (too_old, stay) : (Vec<T>, Vec<T>) = inds.into_iter().partition(|ind| ind.age() > limit);
+            self.set_layer(i, stay);
+
+            if !too_old.is_empty()
+            {
+                let mut next = mem::take(self.layers[i+1].population());
+                next.extend(too_old);
+                self.set_layer(i+1, next);
+            }
+        }
+
+        // Immigration
+        let num_immigrants = cmp::min(self.config.num_immigrants, self.config.layer_size);
+        if num_immigrants > 0
+        {
+            let bottom = mem::take(self.layers[0].population());
+            let keep = cmp::min(bottom.len(), self.config.layer_size - num_immigrants);
+            // The bottom layer is sorted, but `population()` is in insertion
+            // order: keep the best ones by raw score.
+            let mut inds = self.keep_best(bottom, keep);
+            inds.extend(self.immigrants(num_immigrants));
+            self.set_layer(0, inds);
+        }
+
+        // Statistics
+        let top = self.layers.last_mut().unwrap();
+        if top.size() > 0
+        {
+            if self.statistics.alltime_best_pop.is_none()
+            {
+                self.statistics.set_best(top.clone());
+            }
+            else
+            {
+                self.statistics.update(top);
+            }
+        }
+
+        self.current_generation += 1;
+        self.current_generation
+    }
+
+    fn done_internal(&mut self) -> bool
+    {
+        self.current_generation >= self.config.max_generations
+    }
+}
+
+////////////////////////////////////////
+// Tests
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use ::ga::ga_core::*;
+    use ::ga::ga_population::*;
+    use ::ga::ga_random::GARandomCtx;
+    use ::ga::ga_test::*;
+
+    use std::any::Any;
+
+    #[derive(Clone, PartialEq)]
+    struct GAAgedIndividual
+    {
+        raw: f32,
+        age: u32
+    }
+    impl GAIndividual for GAAgedIndividual
+    {
+        fn crossover(&self, other: &GAAgedIndividual, _: &mut Any) -> Box<GAAgedIndividual>
+        {
+            Box::new(GAAgedIndividual { raw: (self.raw + other.raw) / 2.0, age: 0 })
+        }
+        fn mutate(&mut self, _: f32, _: &mut Any) {}
+        fn evaluate(&mut self, _: &mut Any) {}
+        // Never scaled, and ALPS must not rely on it.
+        fn fitness(&self) -> f32 { -self.raw }
+        fn set_fitness(&mut self, _: f32) {}
+        fn raw(&self) -> f32 { self.raw }
+        fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+        fn age(&self) -> u32 { self.age }
+        fn set_age(&mut self, age: u32) { self.age = age; }
+    }
+
+    struct GAAgedFactory;
+    impl GAFactory<GAAgedIndividual> for GAAgedFactory
+    {
+        fn random_population(&mut self, n: usize, sort_order: GAPopulationSortOrder, rng_ctx: &mut GARandomCtx) -> GAPopulation<GAAgedIndividual>
+        {
+            // Fresh individuals come in with some age, the GA must reset it.
+            let inds = (0..n).map(|_| GAAgedIndividual { raw: rng_ctx.gen_range(1.0, 10.0), age: 100 }).collect();
+            GAPopulation::new(inds, sort_order)
+        }
+    }
+
+    #[test]
+    fn test_alps_layers()
+    {
+        ga_test_setup("ga_alps::test_alps_layers");
+
+        let cfg = AlpsGACfg {
+            d_seed : [1; 4],
+            max_generations: 10,
+            num_layers: 3,
+            layer_size: 6,
+            age_gap: 2,
+            num_immigrants: 2,
+            probability_crossover: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(cfg.age_limit(0), Some(2));
+        assert_eq!(cfg.age_limit(1), Some(4));
+        assert_eq!(cfg.age_limit(2), None);
+
+        let mut factory = GAAgedFactory;
+        let mut ga = AlpsGA::new(cfg, &mut factory);
+        ga.initialize();
+        assert_eq!(ga.layers()[0].size(), 6);
+        assert!(ga.layers()[0].population().iter().all(|ind| ind.age() == 0));
+
+        let mut promoted = false;
+        while !ga.done()
+        {
+            ga.step();
+
+            // Young immigrants enter the bottom layer.
+            let immigrants = ga.layers()[0].population().iter().filter(|ind| ind.age() == 0).count();
+            assert!(immigrants >= 2);
+
+            for i in 0..3
+            {
+                assert!(ga.layers()[i].size() <= 6);
+                if let Some(limit) = cfg.age_limit(i)
+                {
+                    assert!(ga.layers()[i].population().iter().all(|ind| ind.age() <= limit));
+                }
+            }
+
+            promoted = promoted || ga.layers()[1].size() > 0;
+        }
+
+        assert!(promoted);
+        // Only promotions feed the top layer.
+        assert!(ga.population().size() > 0);
+        assert!(ga.population().population().iter().all(|ind| ind.age() > 4));
+        // Recorded since the top layer got its first individuals.
+        assert!(ga.statistics().generation_statistics(1).is_some());
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_alps_layers_keep_best_raw()
+    {
+        ga_test_setup("ga_alps::test_alps_layers_keep_best_raw");
+
+        let cfg = AlpsGACfg {
+            d_seed : [1; 4],
+            max_generations: 10,
+            num_layers: 2,
+            layer_size: 3,
+            age_gap: 2,
+            ..Default::default()
+        };
+        let mut factory = GAAgedFactory;
+        let mut ga = AlpsGA::new(cfg, &mut factory);
+        ga.initialize();
+
+        let inds = (1..7).map(|rs| GAAgedIndividual { raw: rs as f32, age: 0 }).collect();
+        ga.set_layer(1, inds);
+
+        let mut raws : Vec<f32> = ga.layers()[1].population().iter().map(|ind| ind.raw()).collect();
+        raws.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(raws, vec![4.0, 5.0, 6.0]);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_alps_best()
+    {
+        ga_test_setup("ga_alps::test_alps_best");
+
+        let cfg = AlpsGACfg {
+            d_seed : [1; 4],
+            max_generations: 10,
+            num_layers: 3,
+            layer_size: 6,
+            age_gap: 2,
+            num_immigrants: 2,
+            probability_crossover: 0.5,
+            ..Default::default()
+        };
+        let mut factory = GAAgedFactory;
+        let mut ga = AlpsGA::new(cfg, &mut factory);
+        ga.initialize();
+
+        // Nothing was promoted to the top layer yet.
+        assert_eq!(ga.population().size(), 0);
+        let best_raw = ga.layers()[0].population().iter().map(|ind| ind.raw()).fold(0.0, f32::max);
+        assert_eq!(ga.best().raw(), best_raw);
+
+        while !ga.done()
+        {
+            ga.step();
+            let best_raw = ga.layers().iter_mut().flat_map(|layer| layer.population().iter().map(|ind| ind.raw()).collect::<Vec<f32>>())
+                                     .fold(0.0, f32::max);
+            assert_eq!(ga.best().raw(), best_raw);
+        }
+
+        ga_test_teardown();
+    }
+}
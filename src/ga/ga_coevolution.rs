@@ -0,0 +1,277 @@
+// Copyright 2016 Revolution Solid & Contributors.
+// author(s): sysnett
+// rust-monster is licensed under a MIT License.
+
+//! Coevolution
+//!
+//! 2 populations that evolve against each other (predator/prey, host/parasite,
+//! ...). Individuals have no fitness of their own, their scores come from
+//! competing against members of the other population.
+use ::ga::ga_core::{GAFlags, GAIndividual};
+use ::ga::ga_population::{GAPopulation, GAPopulationSortOrder};
+use ::ga::ga_random::{GARandomCtx, GASeed};
+use ::ga::ga_selectors::*;
+
+/// Outcome of a competition between an `A` and a `B`: the score each of them
+/// gets, `(a_score, b_score)`.
+pub type GAInteraction<A, B> = fn(&A, &B) -> (f32, f32);
+
+/// Coevolution Genetic Algorithm Config
+#[derive(Copy, Clone, Default)]
+pub struct CoevolutionGACfg
+{
+    pub d_seed : GASeed,
+
+    pub max_generations         : i32,
+
+    // Opponents, drawn at random from the other population, each individual
+    // competes against. 0 means against the whole other population.
+    pub num_opponents           : usize,
+
+    pub probability_crossover   : f32,
+    pub probability_mutation    : f32,
+
+    pub population_sort_order : GAPopulationSortOrder,
+
+    pub flags                   : GAFlags,
+}
+
+/// Coevolution Genetic Algorithm
+///
+/// Each individual's raw (and fitness) score is its average score against
+/// its opponents, as given by the interaction function; `GAIndividual::evaluate`
+/// isn't called. Each generation, population A is bred and evaluated against
+/// B, then B is bred and evaluated against the new A. Breeding is shared with
+/// the other GAs (see `GAPopulation::breed`), and populations don't overlap.
+pub struct CoevolutionGA<A: GAIndividual, B: GAIndividual>
+{
+    current_generation : i32,
+    config : CoevolutionGACfg,
+    population_a : GAPopulation<A>,
+    population_b : GAPopulation<B>,
+    interaction : GAInteraction<A, B>,
+    rng_ctx : GARandomCtx,
+}
+impl<A: GAIndividual + Clone, B: GAIndividual + Clone> CoevolutionGA<A, B>
+{
+    pub fn new(cfg: CoevolutionGACfg,
+               population_a: GAPopulation<A>,
+               population_b: GAPopulation<B>,
+               interaction: GAInteraction<A, B>) -> CoevolutionGA<A, B>
+    {
+        assert!(population_a.size() > 0 && population_b.size() > 0);
+
+        CoevolutionGA
+        {
+            current_generation: 0,
+            config: cfg,
+            population_a: population_a,
+            population_b: population_b,
+            interaction: interaction,
+            rng_ctx: GARandomCtx::from_seed(cfg.d_seed, String::from("CoevolutionGA"))
+        }
+    }
+
+    pub fn population_a(&mut self) -> &mut GAPopulation<A>
+    {
+        &mut self.population_a
+    }
+
+    pub fn population_b(&mut self) -> &mut GAPopulation<B>
+    {
+        &mut self.population_b
+    }
+
+    pub fn initialize(&mut self)
+    {
+        debug!("Coevolution Genetic Algorithm - Initialized");
+        self.evaluate_a();
+        self.evaluate_b();
+    }
+
+    pub fn step(&mut self) -> i32
+    {
+        debug!("Coevolution Genetic Algorithm - Step");
+        // Raw and fitness scores are the same.
+        let size_a = self.population_a.size();
+        let new_a = self.population_a.breed::<GARawScoreSelection>(size_a, self.config.probability_crossover,
+                                                                   self.config.probability_mutation, &mut self.rng_ctx);
+        self.population_a = GAPopulation::new(new_a, self.population_a.order());
+        self.evaluate_a();
+
+        let size_b = self.population_b.size();
+        let new_b = self.population_b.breed::<GARawScoreSelection>(size_b, self.config.probability_crossover,
+                                                                   self.config.probability_mutation, &mut self.rng_ctx);
+        self.population_b = GAPopulation::new(new_b, self.population_b.order());
+        self.evaluate_b();
+
+        self.current_generation += 1;
+        self.current_generation
+    }
+
+    pub fn done(&mut self) -> bool
+    {
+        self.current_generation >= self.config.max_generations
+    }
+
+    // Indices of the opponents of one individual, out of a population of `size`.
+    fn opponents(&mut self, size: usize) -> Vec<usize>
+    {
+        if self.config.num_opponents == 0
+        {
+            (0..size).collect()
+        }
+        else
+        {
+            (0..self.config.num_opponents).map(|_| self.rng_ctx.gen_in(0..size)).collect()
+        }
+    }
+
+    fn evaluate_a(&mut self)
+    {
+        let size_b = self.population_b.size();
+        for i in 0..self.population_a.size()
+        {
+            let opponents = self.opponents(size_b);
+            let interaction = self.interaction;
+            let score = {
+                let a = &self.population_a.population()[i];
+                let b_vec = self.population_b.population();
+                opponents.iter().map(|j| interaction(a, &b_vec[*j]).0).sum::<f32>() / opponents.len() as f32
+            };
+            let a = &mut self.population_a.population()[i];
+            a.set_raw(score);
+            a.set_fitness(score);
+        }
+        self.population_a.sort();
+    }
+
+    fn evaluate_b(&mut self)
+    {
+        let size_a = self.population_a.size();
+        for i in 0..self.population_b.size()
+        {
+            let opponents = self.opponents(size_a);
+            let interaction = self.interaction;
+            let score = {
+                let b = &self.population_b.population()[i];
+                let a_vec = self.population_a.population();
+                opponents.iter().map(|j| interaction(&a_vec[*j], b).1).sum::<f32>() / opponents.len() as f32
+            };
+            let b = &mut self.population_b.population()[i];
+            b.set_raw(score);
+            b.set_fitness(score);
+        }
+        self.population_b.sort();
+    }
+}
+
+////////////////////////////////////////
+// Tests
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use ::ga::ga_core::*;
+    use ::ga::ga_population::*;
+    use ::ga::ga_random::GARandomCtx;
+    use ::ga::ga_test::*;
+
+    use std::any::Any;
+
+    const ROCK : u32 = 0;
+    const PAPER : u32 = 1;
+    const SCISSORS : u32 = 2;
+
+    #[derive(Clone)]
+    struct GAHandIndividual
+    {
+        hand: u32,
+        raw: f32,
+        fitness: f32
+    }
+    impl GAHandIndividual
+    {
+        fn new(hand: u32) -> GAHandIndividual
+        {
+            GAHandIndividual { hand: hand, raw: 0.0, fitness: 0.0 }
+        }
+    }
+    impl GAIndividual for GAHandIndividual
+    {
+        fn crossover(&self, _: &GAHandIndividual, _: &mut Any) -> Box<GAHandIndividual>
+        {
+            Box::new(GAHandIndividual::new(self.hand))
+        }
+        fn mutate(&mut self, probability: f32, ctx: &mut Any)
+        {
+            let rng_ctx = ctx.downcast_mut::<GARandomCtx>().unwrap();
+            if rng_ctx.test_value(probability)
+            {
+                self.hand = rng_ctx.gen_range(0, 3);
+            }
+        }
+        fn evaluate(&mut self, _: &mut Any) { panic!("Coevolution doesn't evaluate individuals") }
+        fn fitness(&self) -> f32 { self.fitness }
+        fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+        fn raw(&self) -> f32 { self.raw }
+        fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+    }
+
+    fn play(a: &GAHandIndividual, b: &GAHandIndividual) -> (f32, f32)
+    {
+        if a.hand == b.hand
+        {
+            (0.5, 0.5)
+        }
+        else if (a.hand + 3 - b.hand) % 3 == 1
+        {
+            (1.0, 0.0)
+        }
+        else
+        {
+            (0.0, 1.0)
+        }
+    }
+
+    fn hands(pop: &mut GAPopulation<GAHandIndividual>, hand: u32) -> usize
+    {
+        pop.population().iter().filter(|ind| ind.hand == hand).count()
+    }
+
+    #[test]
+    fn test_coevolution_rock_paper_scissors()
+    {
+        ga_test_setup("ga_coevolution::test_coevolution_rock_paper_scissors");
+
+        let rocks = GAPopulation::new(vec![GAHandIndividual::new(ROCK); 20], GAPopulationSortOrder::HighIsBest);
+        let scissors = GAPopulation::new(vec![GAHandIndividual::new(SCISSORS); 20], GAPopulationSortOrder::HighIsBest);
+
+        let mut ga = CoevolutionGA::new(CoevolutionGACfg {
+                                            d_seed : [1; 4],
+                                            max_generations: 10,
+                                            num_opponents: 5,
+                                            probability_mutation: 0.2,
+                                            ..Default::default()
+                                        },
+                                        rocks, scissors, play);
+        ga.initialize();
+
+        // Scores come from the other population.
+        assert!(ga.population_a().population().iter().all(|ind| ind.raw() == 1.0 && ind.fitness() == 1.0));
+        assert!(ga.population_b().population().iter().all(|ind| ind.raw() == 0.0));
+
+        while !ga.done()
+        {
+            ga.step();
+        }
+
+        // B learns to beat A's rocks.
+        assert!(hands(ga.population_b(), PAPER) > 0);
+        assert!(ga.population_b().best_by_raw_score().raw() > 0.0);
+        // A moved away from all rocks too.
+        assert!(hands(ga.population_a(), ROCK) < 20);
+
+        ga_test_teardown();
+    }
+}
@@ -6,32 +6,65 @@
 //! Defines the core traits to work with rust-monster
 
 
-use ::ga::ga_population::{GAPopulation, GAPopulationSortOrder};
+use ::ga::ga_population::{GAPopulation, GAPopulationSortBasis, GAPopulationSortOrder};
 use ::ga::ga_random::GARandomCtx;
+use ::ga::ga_statistics::GAStatistics;
 
 use std::any::Any;
+use std::f32;
+use std::sync::mpsc::Sender;
 
 /// Bit Flags for Genetic Algorithm Configuration 
 /// 
-///
+/// `LOG_GENERATION_SUMMARY` logs one line per generation (info level),
+/// `LOG_FULL_STATS` dumps the full population statistics (debug level).
 bitflags!
 {
     pub flags GAFlags: u32
     {
-        const DEBUG_FLAG = 0b00000001
+        const DEBUG_FLAG = 0b00000001,
+        const LOG_GENERATION_SUMMARY = 0b00000010,
+        const LOG_FULL_STATS = 0b00000100
     }
 }
 impl Default for GAFlags
 {
     fn default() -> GAFlags { GAFlags {bits : 0} }
 }
+// Flags are (de)serialized as their bits, unknown bits are dropped.
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for GAFlags
+{
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        self.bits().serialize(serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for GAFlags
+{
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<GAFlags, D::Error>
+    {
+        u32::deserialize(deserializer).map(GAFlags::from_bits_truncate)
+    }
+}
 
 /// Genetic Algorithm Individual
 pub trait GAIndividual
 {
     // Instance
-    fn crossover(&self, other: &Self, &mut Any) -> Box<Self>;
-    fn mutate(&mut self, pMutation: f32, &mut Any);
+    // The context is whatever state the operators need: GAs pass their
+    // `GARandomCtx` to `crossover` and `mutate`, and the user's evaluation
+    // context (if any) to `evaluate`.
+    fn crossover(&self, other: &Self, ctx: &mut Any) -> Box<Self>;
+    /// Crossover that may fail, for encodings where some pairs of parents
+    /// can't be recombined. GAs call this one and, on `None`, keep a clone
+    /// of the first parent. Always succeeds by default.
+    fn try_crossover(&self, other: &Self, ctx: &mut Any) -> Option<Box<Self>>
+    {
+        Some(self.crossover(other, ctx))
+    }
+    fn mutate(&mut self, probability: f32, ctx: &mut Any);
     fn evaluate(&mut self, evaluation_ctx: &mut Any);
     // Fitness score
     fn fitness(&self) -> f32;
@@ -39,6 +72,81 @@ pub trait GAIndividual
     // Raw score
     fn raw(&self) -> f32;
     fn set_raw(&mut self, r: f32);
+    /// Alias of `raw`.
+    fn score(&self) -> f32 { self.raw() }
+    // Full precision scores
+    /// Raw score at `f64` precision. Populations sort on it, and keep
+    /// extremes of it in their statistics. Individuals whose scores need more
+    /// precision than `f32` store them as `f64`, override `raw_f64` and
+    /// `fitness_f64`, and return the rounded scores from `raw` and `fitness`.
+    /// The `f32` score, widened, by default.
+    fn raw_f64(&self) -> f64 { self.raw() as f64 }
+    /// Fitness score at `f64` precision, see `raw_f64`.
+    fn fitness_f64(&self) -> f64 { self.fitness() as f64 }
+    /// One score per objective, for multi-objective utilities (see
+    /// `GAMultiObjectiveIndividual`). The raw score alone by default: a
+    /// single-objective individual is the degenerate one-objective case.
+    fn objectives(&self) -> Vec<f32> { vec![self.raw()] }
+
+    // Self-adaptive operator rates
+    /// Crossover probability encoded in the individual. When `Some`, GAs use
+    /// it instead of their configured crossover probability.
+    fn crossover_rate(&self) -> Option<f32> { None }
+    /// Mutation probability encoded in the individual. When `Some`, GAs use
+    /// it instead of their configured mutation probability.
+    fn mutate_rate(&self) -> Option<f32> { None }
+
+    // Age
+    /// Number of generations the individual's genetic material has been
+    /// around. Used by age-layered GAs (see `AlpsGA`), which need individuals
+    /// to implement both `age` and `set_age`. Always 0 by default.
+    fn age(&self) -> u32 { 0 }
+    fn set_age(&mut self, _age: u32) {}
+
+    // MEMETIC ALGORITHMS
+    /// Local search from the individual (e.g. hill climbing), for memetic
+    /// GAs. Improves the genes in place and returns the improved raw score,
+    /// GAs set it (see `GALocalSearchMode` for how they use it). No search by
+    /// default: the genes are left as they are and the raw score returned.
+    fn local_optimize(&mut self, _ctx: &mut Any) -> f32 { self.raw() }
+}
+
+/// Diversity
+///
+/// Individuals with a distance between genomes (permutations, vectors, ...).
+/// Gives their populations a genotypic diversity, the mean pairwise distance
+/// (see `GAPopulation::update_diversity`), and is what clustering, nearest
+/// neighbors and niching measure.
+pub trait GADiversity: GAIndividual
+{
+    /// Distance between the genomes of 2 individuals.
+    fn distance(&self, other: &Self) -> f32;
+    /// Similarity between 2 individuals, in (0, 1]. 1 for identical ones.
+    fn similarity(&self, other: &Self) -> f32 { 1.0 / (1.0 + self.distance(other)) }
+}
+
+/// Multi-Objective Individual
+///
+/// Individuals scored on several objectives at once, through
+/// `GAIndividual::objectives`. `raw` and `fitness` still drive the
+/// single-objective machinery (selectors, statistics, ...), multi-objective
+/// utilities use `objectives`. All individuals of a population must have the
+/// same number of objectives.
+///
+/// Implemented for every individual, so standard individuals can be used
+/// with multi-objective utilities as is.
+pub trait GAMultiObjectiveIndividual: GAIndividual {}
+impl<T: GAIndividual> GAMultiObjectiveIndividual for T {}
+
+
+/// Batch Evaluation
+///
+/// Evaluates a whole set of individuals at once. Implement it when fitness is
+/// much cheaper to compute for the population as a whole (e.g. with matrix
+/// operations) than individual by individual.
+pub trait GABatchEvaluate<T: GAIndividual>
+{
+    fn evaluate_batch(&self, inds: &mut [T]);
 }
 
 
@@ -84,8 +192,44 @@ pub trait GeneticAlgorithm<T: GAIndividual>
         self.done_internal()
     }
 
+    /// Run to completion (call `initialize` first), sending
+    /// `(generation, best raw score)` through `tx` after each generation.
+    ///
+    /// The best raw score is the one of the last generation recorded in
+    /// `statistics()` (see `GAStatistics::best_raw_series`), so generations
+    /// that couldn't be recorded (e.g. ended with an empty population) repeat
+    /// it. NaN if no generation was ever recorded.
+    ///
+    /// Stops early, after the current generation, if the receiver is dropped.
+    fn run_with_progress(&mut self, tx: Sender<(i32, f32)>)
+    {
+        while !self.done()
+        {
+            let generation = self.step();
+            let best = self.statistics().best_raw_series().last().cloned().unwrap_or(f32::NAN);
+            if tx.send((generation, best)).is_err()
+            {
+                debug!("Genetic Algorithm - Progress receiver dropped, stopping");
+                return;
+            }
+        }
+    }
+
+    /// Best individual of the current population, by raw score. Sorts the
+    /// population if needed.
+    ///
+    /// Panics if the population is empty.
+    fn best(&mut self) -> &T
+    {
+        let pop = self.population();
+        pop.sort();
+        pop.individual(0, GAPopulationSortBasis::Raw)
+    }
+
     // IMPLEMENTATION SPECIFIC
     fn population(&mut self) -> &mut GAPopulation<T>;
+    /// Generation-over-generation statistics of the run.
+    fn statistics(&self) -> &GAStatistics<T>;
 
     fn initialize_internal(&mut self) {}
     fn step_internal(&mut self) -> i32 { 0 }
@@ -0,0 +1,967 @@
+// Copyright 2016 Revolution Solid & Contributors.
+// author(s): sysnett
+// rust-monster is licensed under a MIT License.
+
+//! GA Crossover Operators
+//!
+//! Reusable crossover operators for individuals whose genome is a vector
+//! of genes.
+//!
+//! All operators implement `GACrossover`. Individuals that implement
+//! `HasGenes` get a default crossover (`HasGenes::crossover_genes`) that
+//! uses one of the `GAVectorCrossover` operators, and a default mutation
+//! (`HasGenes::mutate_genes`); `ga_genes_operators!` implements
+//! `GAIndividual::crossover` and `GAIndividual::mutate` with them, so they
+//! don't need to hand-write either.
+use ::ga::ga_core::GAIndividual;
+use ::ga::ga_mutation::{flip_mutate, gaussian_mutate, gaussian_mutate_f64};
+use ::ga::ga_random::GARandomCtx;
+
+use std::any::Any;
+use std::cmp;
+
+/// Standard deviation of the gaussian mutation of real genes.
+pub const GA_GENE_GAUSSIAN_STD_DEV : f32 = 1.0;
+
+/// Gene types that the vector operators work with.
+pub trait GAGene: Copy
+{
+    /// Blend 2 genes, `alpha` being the weight of the first one.
+    fn blend(first: Self, second: Self, alpha: f32) -> Self;
+
+    /// Mutate each gene with the given probability.
+    fn mutate_genes(genes: &mut [Self], probability: f32, rng_ctx: &mut GARandomCtx);
+
+    /// Mutate each gene with the given probability, real genes by gaussian
+    /// noise with standard deviation `std_dev`. Other genes ignore it,
+    /// `mutate_genes` by default.
+    fn mutate_genes_with_std_dev(genes: &mut [Self], probability: f32, _std_dev: f64, rng_ctx: &mut GARandomCtx)
+    {
+        Self::mutate_genes(genes, probability, rng_ctx);
+    }
+}
+
+/// Real genes: weighted average blend, gaussian mutation.
+impl GAGene for f32
+{
+    fn blend(first: f32, second: f32, alpha: f32) -> f32
+    {
+        alpha*first + (1.0-alpha)*second
+    }
+
+    fn mutate_genes(genes: &mut [f32], probability: f32, rng_ctx: &mut GARandomCtx)
+    {
+        gaussian_mutate(genes, probability, GA_GENE_GAUSSIAN_STD_DEV, rng_ctx);
+    }
+
+    fn mutate_genes_with_std_dev(genes: &mut [f32], probability: f32, std_dev: f64, rng_ctx: &mut GARandomCtx)
+    {
+        gaussian_mutate(genes, probability, std_dev as f32, rng_ctx);
+    }
+}
+
+/// Double precision real genes, like `f32` ones.
+impl GAGene for f64
+{
+    fn blend(first: f64, second: f64, alpha: f32) -> f64
+    {
+        let alpha = alpha as f64;
+        alpha*first + (1.0-alpha)*second
+    }
+
+    fn mutate_genes(genes: &mut [f64], probability: f32, rng_ctx: &mut GARandomCtx)
+    {
+        gaussian_mutate_f64(genes, probability, GA_GENE_GAUSSIAN_STD_DEV as f64, rng_ctx);
+    }
+
+    fn mutate_genes_with_std_dev(genes: &mut [f64], probability: f32, std_dev: f64, rng_ctx: &mut GARandomCtx)
+    {
+        gaussian_mutate_f64(genes, probability, std_dev, rng_ctx);
+    }
+}
+
+/// Boolean genes: the blend takes the gene of the heavier parent, mutation
+/// flips bits.
+impl GAGene for bool
+{
+    fn blend(first: bool, second: bool, alpha: f32) -> bool
+    {
+        if alpha >= 0.5 { first } else { second }
+    }
+
+    fn mutate_genes(genes: &mut [bool], probability: f32, rng_ctx: &mut GARandomCtx)
+    {
+        flip_mutate(genes, probability, rng_ctx);
+    }
+}
+
+/// Vector Crossover Operators
+///
+/// Operators that produce a child gene vector out of 2 parent gene vectors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GAVectorCrossover
+{
+    /// Cut both parents at a random point, the child takes the genes before
+    /// the cut from the first parent and the rest from the second one.
+    SinglePoint,
+    /// Each gene is taken from either parent with equal probability.
+    Uniform,
+    /// Each gene is a blend of both parents' genes (see `GAGene::blend`),
+    /// `alpha*first + (1-alpha)*second` for real genes.
+    Arithmetic(f32),
+}
+
+impl Default for GAVectorCrossover
+{
+    fn default() -> GAVectorCrossover { GAVectorCrossover::SinglePoint }
+}
+
+impl GAVectorCrossover
+{
+    /// Cross 2 gene vectors over.
+    ///
+    /// When the parents have different lengths, the child has the length of
+    /// the shortest one.
+    pub fn crossover<G: GAGene>(&self, first: &[G], second: &[G], rng_ctx: &mut GARandomCtx) -> Vec<G>
+    {
+        let len = cmp::min(first.len(), second.len());
+
+        match *self
+        {
+            GAVectorCrossover::SinglePoint =>
+            {
+                NPointCrossover { n: 1 }.cross(first, second, rng_ctx)
+            },
+            GAVectorCrossover::Uniform =>
+            {
+                UniformCrossover { swap_prob: 0.5 }.cross(first, second, rng_ctx)
+            },
+            GAVectorCrossover::Arithmetic(alpha) =>
+            {
+                (0..len).map(|i| G::blend(first[i], second[i], alpha)).collect()
+            }
+        }
+    }
+}
+
+/// Crossover operator over slice genomes of `G` genes.
+///
+/// Operators that only move genes around work with any gene type. Unless
+/// they say otherwise, when the parents have different lengths the child has
+/// the length of the shortest one.
+pub trait GACrossover<G>
+{
+    fn cross(&self, first: &[G], second: &[G], rng_ctx: &mut GARandomCtx) -> Vec<G>;
+}
+
+impl<G: GAGene> GACrossover<G> for GAVectorCrossover
+{
+    fn cross(&self, first: &[G], second: &[G], rng_ctx: &mut GARandomCtx) -> Vec<G>
+    {
+        self.crossover(first, second, rng_ctx)
+    }
+}
+
+/// Cut both parents at `n` distinct random points, the child takes genes
+/// alternately from the first and the second parent between cuts, starting
+/// with the first.
+///
+/// Genomes of length `len` have `len - 1` cut points, `n` is capped to that.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NPointCrossover
+{
+    pub n: usize
+}
+
+impl<G: Copy> GACrossover<G> for NPointCrossover
+{
+    fn cross(&self, first: &[G], second: &[G], rng_ctx: &mut GARandomCtx) -> Vec<G>
+    {
+        let len = cmp::min(first.len(), second.len());
+        if len < 2
+        {
+            return first[..len].to_vec();
+        }
+
+        // Cuts in 1..len, so each segment has at least 1 gene.
+        let mut cuts : Vec<usize> = rng_ctx.sample_indices(len - 1, cmp::min(self.n, len - 1))
+                                           .iter().map(|c| c + 1).collect();
+        cuts.sort();
+        cuts.push(len);
+
+        let mut child = Vec::with_capacity(len);
+        let mut start = 0;
+        for (i, cut) in cuts.iter().enumerate()
+        {
+            let parent = if i % 2 == 0 { first } else { second };
+            child.extend_from_slice(&parent[start..*cut]);
+            start = *cut;
+        }
+        child
+    }
+}
+
+/// Each gene is taken from the second parent with probability `swap_prob`,
+/// from the first one otherwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UniformCrossover
+{
+    pub swap_prob: f32
+}
+
+impl<G: Copy> GACrossover<G> for UniformCrossover
+{
+    fn cross(&self, first: &[G], second: &[G], rng_ctx: &mut GARandomCtx) -> Vec<G>
+    {
+        let len = cmp::min(first.len(), second.len());
+        (0..len).map(|i| if rng_ctx.test_value(self.swap_prob) { second[i] } else { first[i] }).collect()
+    }
+}
+
+/// Blend crossover (BLX-alpha) of real genes.
+///
+/// Each gene is drawn uniformly from the interval spanned by both parents'
+/// genes, extended by `alpha` times its length on each side. Genes may land
+/// outside of the parents' range, individuals with bounds clamp them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlxAlphaCrossover
+{
+    pub alpha: f64
+}
+
+impl Default for BlxAlphaCrossover
+{
+    fn default() -> BlxAlphaCrossover { BlxAlphaCrossover { alpha: 0.5 } }
+}
+
+impl GACrossover<f64> for BlxAlphaCrossover
+{
+    fn cross(&self, first: &[f64], second: &[f64], rng_ctx: &mut GARandomCtx) -> Vec<f64>
+    {
+        first.iter().zip(second.iter()).map(|(f, s)|
+        {
+            let low = f.min(*s);
+            let high = f.max(*s);
+            let extension = self.alpha * (high - low);
+            if high - low + 2.0 * extension <= 0.0
+            {
+                return low;
+            }
+            rng_ctx.gen_range(low - extension, high + extension)
+        }).collect()
+    }
+}
+
+/// Simulated Binary Crossover (SBX) of real genes.
+///
+/// Mimics the spread of single point crossover on binary strings: children
+/// genes fall around the parents' genes, closer for larger distribution
+/// indexes `eta`. Like BLX-alpha, genes may land outside of the parents'
+/// range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimulatedBinaryCrossover
+{
+    pub eta: f64
+}
+
+impl Default for SimulatedBinaryCrossover
+{
+    fn default() -> SimulatedBinaryCrossover { SimulatedBinaryCrossover { eta: 2.0 } }
+}
+
+impl GACrossover<f64> for SimulatedBinaryCrossover
+{
+    fn cross(&self, first: &[f64], second: &[f64], rng_ctx: &mut GARandomCtx) -> Vec<f64>
+    {
+        first.iter().zip(second.iter()).map(|(f, s)|
+        {
+            let u = rng_ctx.gen::<f64>();
+            let beta = if u <= 0.5
+                {
+                    (2.0 * u).powf(1.0 / (self.eta + 1.0))
+                }
+                else
+                {
+                    (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (self.eta + 1.0))
+                };
+            // 0.5 * ((1 + beta) * f + (1 - beta) * s), exact for equal genes.
+            0.5 * (f + s) + 0.5 * beta * (f - s)
+        }).collect()
+    }
+}
+
+/// Order Crossover (OX) for permutations of `0..n`.
+///
+/// The child keeps a random segment of the first parent in place, and the
+/// rest of the genes in the order they appear in the second parent, starting
+/// after the segment and wrapping around.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OrderCrossover;
+
+impl GACrossover<usize> for OrderCrossover
+{
+    /// Cross 2 permutations over, keeping a random (non-empty) segment of
+    /// `first`.
+    ///
+    /// Panics if the parents aren't permutations of `0..n` of the same length.
+    fn cross(&self, first: &[usize], second: &[usize], rng_ctx: &mut GARandomCtx) -> Vec<usize>
+    {
+        assert_permutations(first, second);
+        let (start, end) = random_segment(first.len(), rng_ctx);
+        self.cross_segment(first, second, start, end)
+    }
+}
+
+impl OrderCrossover
+{
+    /// Cross 2 permutations over, keeping `first[start..end]`.
+    pub fn cross_segment(&self, first: &[usize], second: &[usize], start: usize, end: usize) -> Vec<usize>
+    {
+        assert_permutations(first, second);
+        assert!(start <= end && end <= first.len(), "OrderCrossover: bad segment [{}, {})", start, end);
+
+        let n = first.len();
+        let mut in_segment = vec![false; n];
+        for gene in &first[start..end]
+        {
+            in_segment[*gene] = true;
+        }
+
+        let mut child = first.to_vec();
+        let mut from_second = (0..n).map(|i| second[(end + i) % n]).filter(|gene| !in_segment[*gene]);
+        for i in 0..n - (end - start)
+        {
+            child[(end + i) % n] = from_second.next().unwrap();
+        }
+        child
+    }
+}
+
+/// Partially Mapped Crossover (PMX) for permutations of `0..n`.
+///
+/// The child keeps a random segment of the first parent in place. Genes of
+/// the second parent also keep their positions, unless taken by the segment,
+/// in which case they move following the mapping between both parents'
+/// segments.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PartiallyMappedCrossover;
+
+impl GACrossover<usize> for PartiallyMappedCrossover
+{
+    /// Cross 2 permutations over, keeping a random (non-empty) segment of
+    /// `first`.
+    ///
+    /// Panics if the parents aren't permutations of `0..n` of the same length.
+    fn cross(&self, first: &[usize], second: &[usize], rng_ctx: &mut GARandomCtx) -> Vec<usize>
+    {
+        assert_permutations(first, second);
+        let (start, end) = random_segment(first.len(), rng_ctx);
+        self.cross_segment(first, second, start, end)
+    }
+}
+
+impl PartiallyMappedCrossover
+{
+    /// Cross 2 permutations over, keeping `first[start..end]`.
+    pub fn cross_segment(&self, first: &[usize], second: &[usize], start: usize, end: usize) -> Vec<usize>
+    {
+        assert_permutations(first, second);
+        assert!(start <= end && end <= first.len(), "PartiallyMappedCrossover: bad segment [{}, {})", start, end);
+
+        let n = first.len();
+        let mut position_in_second = vec![0; n];
+        for (i, gene) in second.iter().enumerate()
+        {
+            position_in_second[*gene] = i;
+        }
+
+        let mut child : Vec<Option<usize>> = vec![None; n];
+        let mut in_segment = vec![false; n];
+        for i in start..end
+        {
+            child[i] = Some(first[i]);
+            in_segment[first[i]] = true;
+        }
+
+        // Genes of second's segment displaced by first's: follow the mapping
+        // out of the segment.
+        for (i, gene) in second.iter().cloned().enumerate().take(end).skip(start)
+        {
+            if in_segment[gene]
+            {
+                continue;
+            }
+
+            let mut position = i;
+            while position >= start && position < end
+            {
+                position = position_in_second[first[position]];
+            }
+            child[position] = Some(gene);
+        }
+
+        child.iter().enumerate().map(|(i, gene)| gene.unwrap_or(second[i])).collect()
+    }
+}
+
+// Panics unless both genomes are permutations of 0..n, same n.
+fn assert_permutations(first: &[usize], second: &[usize])
+{
+    assert_eq!(first.len(), second.len(), "Permutation crossover: parents of different lengths");
+    for genome in &[first, second]
+    {
+        let mut seen = vec![false; genome.len()];
+        for gene in genome.iter()
+        {
+            assert!(*gene < seen.len() && !seen[*gene], "Permutation crossover: parent isn't a permutation of 0..{}", seen.len());
+            seen[*gene] = true;
+        }
+    }
+}
+
+// Random non-empty segment [start, end) of a genome of length n (empty if
+// n is 0).
+fn random_segment(n: usize, rng_ctx: &mut GARandomCtx) -> (usize, usize)
+{
+    if n == 0
+    {
+        return (0, 0);
+    }
+    let mut bounds = rng_ctx.sample_indices(n + 1, 2);
+    bounds.sort();
+    (bounds[0], bounds[1])
+}
+
+/// Individuals backed by a vector of genes.
+///
+/// Exposing the genes gives access to the reusable operators of this module.
+/// `ga_genes_operators!` implements `GAIndividual::crossover` and
+/// `GAIndividual::mutate` with `crossover_genes` and `mutate_genes`.
+///
+/// Genes are real (`f32`) by default, `HasGenes<bool>` is a bit string.
+pub trait HasGenes<G: GAGene = f32>: GAIndividual + Sized
+{
+    fn genes(&self) -> &[G];
+    fn genes_mut(&mut self) -> &mut [G];
+    fn from_genes(genes: Vec<G>) -> Self;
+
+    /// Child of this individual with `genes`, built by the crossovers.
+    /// `from_genes` by default, individuals with more state than their genes
+    /// (bounds, operators, ...) override it to pass that state on.
+    fn child_with_genes(&self, genes: Vec<G>) -> Self
+    {
+        Self::from_genes(genes)
+    }
+
+    /// Standard deviation of the gaussian noise `mutate_genes` adds to real
+    /// genes. `GA_GENE_GAUSSIAN_STD_DEV` by default.
+    fn genes_mutation_std_dev(&self) -> f64
+    {
+        GA_GENE_GAUSSIAN_STD_DEV as f64
+    }
+
+    /// Operator used by `crossover_genes`. Single point by default.
+    fn genes_crossover(&self) -> GAVectorCrossover
+    {
+        GAVectorCrossover::default()
+    }
+
+    /// Default crossover for gene vector individuals.
+    ///
+    /// Expects a `GARandomCtx` as context (as `SimpleGeneticAlgorithm` passes).
+    fn crossover_genes(&self, other: &Self, ctx: &mut Any) -> Box<Self>
+    {
+        match ctx.downcast_mut::<GARandomCtx>()
+        {
+            Some(rng_ctx) =>
+            {
+                let genes = self.genes_crossover().crossover(self.genes(), other.genes(), rng_ctx);
+                Box::new(self.child_with_genes(genes))
+            },
+            None =>
+            {
+                panic!("Incorrect type passed for context");
+            }
+        }
+    }
+
+    /// Crossover with a `GACrossover` operator, for individuals that
+    /// delegate `GAIndividual::crossover` to one.
+    ///
+    /// Expects a `GARandomCtx` as context.
+    fn crossover_genes_with<C: GACrossover<G>>(&self, other: &Self, op: &C, ctx: &mut Any) -> Box<Self>
+    {
+        match ctx.downcast_mut::<GARandomCtx>()
+        {
+            Some(rng_ctx) =>
+            {
+                Box::new(self.child_with_genes(op.cross(self.genes(), other.genes(), rng_ctx)))
+            },
+            None =>
+            {
+                panic!("Incorrect type passed for context");
+            }
+        }
+    }
+
+    /// Default mutation for gene vector individuals.
+    ///
+    /// Each gene mutates with the given probability: gaussian noise for real
+    /// genes (see `genes_mutation_std_dev`), a flip for boolean ones. Expects
+    /// a `GARandomCtx` as context.
+    fn mutate_genes(&mut self, probability: f32, ctx: &mut Any)
+    {
+        match ctx.downcast_mut::<GARandomCtx>()
+        {
+            Some(rng_ctx) =>
+            {
+                let std_dev = self.genes_mutation_std_dev();
+                G::mutate_genes_with_std_dev(self.genes_mut(), probability, std_dev, rng_ctx);
+            },
+            None =>
+            {
+                panic!("Incorrect type passed for context");
+            }
+        }
+    }
+}
+
+/// Implement `GAIndividual::crossover` and `GAIndividual::mutate` of a
+/// `HasGenes` individual with the default `HasGenes::crossover_genes` and
+/// `HasGenes::mutate_genes`. Invoke it inside the `impl GAIndividual` block.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate rust_monster;
+/// use rust_monster::ga::ga_core::GAIndividual;
+/// use rust_monster::ga::ga_crossover::HasGenes;
+/// use std::any::Any;
+///
+/// #[derive(Clone)]
+/// struct Genes { genes: Vec<f32>, raw: f32, fitness: f32 }
+///
+/// impl HasGenes for Genes
+/// {
+///     fn genes(&self) -> &[f32] { &self.genes }
+///     fn genes_mut(&mut self) -> &mut [f32] { &mut self.genes }
+///     fn from_genes(genes: Vec<f32>) -> Genes { Genes { genes: genes, raw: 0.0, fitness: 0.0 } }
+/// }
+///
+/// impl GAIndividual for Genes
+/// {
+///     ga_genes_operators!();
+///
+///     fn evaluate(&mut self, _: &mut Any) { self.raw = self.genes.iter().sum(); }
+///     fn fitness(&self) -> f32 { self.fitness }
+///     fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+///     fn raw(&self) -> f32 { self.raw }
+///     fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+/// }
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! ga_genes_operators
+{
+    () =>
+    {
+        fn crossover(&self, other: &Self, ctx: &mut ::std::any::Any) -> Box<Self>
+        {
+            $crate::ga::ga_crossover::HasGenes::crossover_genes(self, other, ctx)
+        }
+
+        fn mutate(&mut self, probability: f32, ctx: &mut ::std::any::Any)
+        {
+            $crate::ga::ga_crossover::HasGenes::mutate_genes(self, probability, ctx)
+        }
+    }
+}
+
+////////////////////////////////////////
+// Tests
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use ::ga::ga_core::GAIndividual;
+    use ::ga::ga_random::GARandomCtx;
+    use ::ga::ga_test::*;
+
+    use std::any::Any;
+
+    #[derive(Clone)]
+    struct GAGenesIndividual
+    {
+        genes: Vec<f32>,
+        raw: f32,
+        fitness: f32
+    }
+    impl HasGenes for GAGenesIndividual
+    {
+        fn genes(&self) -> &[f32] { &self.genes }
+        fn genes_mut(&mut self) -> &mut [f32] { &mut self.genes }
+        fn from_genes(genes: Vec<f32>) -> GAGenesIndividual
+        {
+            GAGenesIndividual { genes: genes, raw: 0.0, fitness: 0.0 }
+        }
+    }
+    impl GAIndividual for GAGenesIndividual
+    {
+        ga_genes_operators!();
+        fn evaluate(&mut self, _: &mut Any) {}
+        fn fitness(&self) -> f32 { self.fitness }
+        fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+        fn raw(&self) -> f32 { self.raw }
+        fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+    }
+
+    #[derive(Clone)]
+    struct GABitsIndividual
+    {
+        bits: Vec<bool>
+    }
+    impl HasGenes<bool> for GABitsIndividual
+    {
+        fn genes(&self) -> &[bool] { &self.bits }
+        fn genes_mut(&mut self) -> &mut [bool] { &mut self.bits }
+        fn from_genes(bits: Vec<bool>) -> GABitsIndividual
+        {
+            GABitsIndividual { bits: bits }
+        }
+    }
+    impl GAIndividual for GABitsIndividual
+    {
+        ga_genes_operators!();
+        fn evaluate(&mut self, _: &mut Any) {}
+        fn fitness(&self) -> f32 { 0.0 }
+        fn set_fitness(&mut self, _: f32) {}
+        fn raw(&self) -> f32 { 0.0 }
+        fn set_raw(&mut self, _: f32) {}
+    }
+
+    #[test]
+    fn test_has_genes_default_crossover()
+    {
+        ga_test_setup("ga_crossover::test_has_genes_default_crossover");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_has_genes_default_crossover_rng"));
+
+        let mom = GAGenesIndividual::from_genes(vec![0.0; 10]);
+        let dad = GAGenesIndividual::from_genes(vec![1.0; 10]);
+
+        for _ in 0..20
+        {
+            let child = mom.crossover(&dad, &mut rng_ctx);
+            assert_eq!(child.genes().len(), 10);
+
+            // Single point: some 0s (from mom) followed by some 1s (from dad).
+            let cut = child.genes().iter().position(|g| *g == 1.0).unwrap();
+            assert!(cut > 0);
+            assert!(child.genes()[..cut].iter().all(|g| *g == 0.0));
+            assert!(child.genes()[cut..].iter().all(|g| *g == 1.0));
+        }
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_has_genes_default_crossover_wrong_ctx()
+    {
+        ga_test_setup("ga_crossover::test_has_genes_default_crossover_wrong_ctx");
+        let mom = GAGenesIndividual::from_genes(vec![0.0; 10]);
+        let dad = GAGenesIndividual::from_genes(vec![1.0; 10]);
+        let mut not_a_rng = 0;
+        mom.crossover(&dad, &mut not_a_rng);
+    }
+
+    #[test]
+    fn test_vector_crossover_operators()
+    {
+        ga_test_setup("ga_crossover::test_vector_crossover_operators");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_vector_crossover_operators_rng"));
+
+        let first = vec![0.0, 0.0, 0.0, 0.0];
+        let second = vec![1.0, 1.0, 1.0, 1.0];
+
+        let uniform = GAVectorCrossover::Uniform.crossover(&first, &second, &mut rng_ctx);
+        assert_eq!(uniform.len(), 4);
+        assert!(uniform.iter().all(|g| *g == 0.0 || *g == 1.0));
+
+        let arithmetic = GAVectorCrossover::Arithmetic(0.25).crossover(&first, &second, &mut rng_ctx);
+        assert_eq!(arithmetic, vec![0.75; 4]);
+
+        // Shortest parent wins.
+        let short = GAVectorCrossover::SinglePoint.crossover(&first[..1], &second, &mut rng_ctx);
+        assert_eq!(short, vec![0.0]);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_has_genes_default_mutate()
+    {
+        ga_test_setup("ga_crossover::test_has_genes_default_mutate");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_has_genes_default_mutate_rng"));
+
+        let mut ind = GAGenesIndividual::from_genes(vec![0.0; 10]);
+        ind.mutate(0.0, &mut rng_ctx);
+        assert_eq!(ind.genes(), &[0.0; 10][..]);
+
+        ind.mutate(1.0, &mut rng_ctx);
+        assert!(ind.genes().iter().all(|g| *g != 0.0));
+
+        let mut bits = GABitsIndividual::from_genes(vec![false; 10]);
+        bits.mutate(1.0, &mut rng_ctx);
+        assert_eq!(bits.genes(), &[true; 10][..]);
+
+        let child = bits.crossover(&GABitsIndividual::from_genes(vec![false; 10]), &mut rng_ctx);
+        assert_eq!(child.genes().len(), 10);
+
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn test_n_point_crossover()
+    {
+        ga_test_setup("ga_crossover::test_n_point_crossover");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_n_point_crossover_rng"));
+
+        let first = vec![0; 10];
+        let second = vec![1; 10];
+        for n in 1..12
+        {
+            let child = NPointCrossover { n: n }.cross(&first, &second, &mut rng_ctx);
+            assert_eq!(child.len(), 10);
+            assert_eq!(child[0], 0);
+
+            // n cuts make n changes of parent (capped to 9 cuts).
+            let changes = child.windows(2).filter(|w| w[0] != w[1]).count();
+            assert_eq!(changes, cmp::min(n, 9));
+        }
+
+        // Shortest parent wins.
+        assert_eq!(NPointCrossover { n: 3 }.cross(&first[..1], &second, &mut rng_ctx), vec![0]);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_single_point_is_one_point()
+    {
+        ga_test_setup("ga_crossover::test_single_point_is_one_point");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_single_point_is_one_point_rng"));
+        let mut rng_ctx_2 = GARandomCtx::from_seed([1,2,3,4], String::from("test_single_point_is_one_point_rng_2"));
+
+        let first : Vec<f32> = (0..20).map(|g| g as f32).collect();
+        let second : Vec<f32> = (100..120).map(|g| g as f32).collect();
+        for _ in 0..50
+        {
+            assert_eq!(GAVectorCrossover::SinglePoint.cross(&first, &second, &mut rng_ctx),
+                       NPointCrossover { n: 1 }.cross(&first, &second, &mut rng_ctx_2));
+        }
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_uniform_crossover()
+    {
+        ga_test_setup("ga_crossover::test_uniform_crossover");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_uniform_crossover_rng"));
+
+        let first : Vec<u8> = (0..20).collect();
+        let second : Vec<u8> = (100..120).collect();
+        assert_eq!(UniformCrossover { swap_prob: 0.0 }.cross(&first, &second, &mut rng_ctx), first);
+        assert_eq!(UniformCrossover { swap_prob: 1.0 }.cross(&first, &second, &mut rng_ctx), second);
+
+        let child = UniformCrossover { swap_prob: 0.5 }.cross(&first, &second, &mut rng_ctx);
+        assert!(child.iter().enumerate().all(|(i, g)| *g == first[i] || *g == second[i]));
+
+        // Delegating individuals.
+        let mom = GABitsIndividual::from_genes(vec![false; 10]);
+        let dad = GABitsIndividual::from_genes(vec![true; 10]);
+        let child = mom.crossover_genes_with(&dad, &UniformCrossover { swap_prob: 0.0 }, &mut rng_ctx);
+        assert_eq!(child.genes(), mom.genes());
+
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn test_blx_alpha_crossover()
+    {
+        ga_test_setup("ga_crossover::test_blx_alpha_crossover");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_blx_alpha_crossover_rng"));
+
+        let first = vec![0.0, 1.0, -3.0, 2.0];
+        let second = vec![1.0, 1.0, 5.0, -2.0];
+        let blx = BlxAlphaCrossover { alpha: 0.5 };
+        let mut outside = 0;
+        for _ in 0..1000
+        {
+            let child = blx.cross(&first, &second, &mut rng_ctx);
+            assert_eq!(child.len(), 4);
+            for (i, g) in child.iter().enumerate()
+            {
+                let low = first[i].min(second[i]);
+                let high = first[i].max(second[i]);
+                let extension = blx.alpha * (high - low);
+                assert!(*g >= low - extension && *g <= high + extension, "{} not in [{}, {}]", g, low - extension, high + extension);
+                if *g < low || *g > high
+                {
+                    outside += 1;
+                }
+            }
+        }
+        // Equal genes have an empty interval, the rest land outside of the
+        // parents' range half of the time with alpha 0.5.
+        assert!(outside > 1200 && outside < 1800, "{} genes outside", outside);
+
+        // Alpha 0 stays within the parents' range.
+        let child = BlxAlphaCrossover { alpha: 0.0 }.cross(&first, &second, &mut rng_ctx);
+        assert!(child.iter().enumerate().all(|(i, g)| *g >= first[i].min(second[i]) && *g <= first[i].max(second[i])));
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_simulated_binary_crossover()
+    {
+        ga_test_setup("ga_crossover::test_simulated_binary_crossover");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_simulated_binary_crossover_rng"));
+
+        let sbx = SimulatedBinaryCrossover::default();
+        let first = vec![2.0, -1.0, 0.5];
+        assert_eq!(sbx.cross(&first, &first, &mut rng_ctx), first);
+
+        // The child spreads around the first parent.
+        let second = vec![4.0, 1.0, 0.5];
+        let mut mean = vec![0.0; 3];
+        for _ in 0..1000
+        {
+            let child = sbx.cross(&first, &second, &mut rng_ctx);
+            assert_eq!(child[2], 0.5);
+            for (m, g) in mean.iter_mut().zip(child.iter())
+            {
+                *m += g / 1000.0;
+            }
+        }
+        for i in 0..2
+        {
+            assert!((mean[i] - first[i]).abs() < (mean[i] - second[i]).abs(), "{:?}", mean);
+        }
+
+        ga_test_teardown();
+    }
+
+
+    fn is_permutation(genome: &[usize]) -> bool
+    {
+        let mut sorted = genome.to_vec();
+        sorted.sort();
+        sorted == (0..genome.len()).collect::<Vec<usize>>()
+    }
+
+    #[test]
+    fn test_order_crossover()
+    {
+        ga_test_setup("ga_crossover::test_order_crossover");
+
+        let first : Vec<usize> = (0..9).collect();
+        let second = vec![8, 2, 6, 7, 1, 5, 4, 0, 3];
+        assert_eq!(OrderCrossover.cross_segment(&first, &second, 3, 7), vec![2, 7, 1, 3, 4, 5, 6, 0, 8]);
+        assert_eq!(OrderCrossover.cross_segment(&first, &second, 0, 9), first);
+        assert_eq!(OrderCrossover.cross_segment(&first, &second, 4, 4), second);
+
+        for seed in 1..20
+        {
+            let mut rng_ctx = GARandomCtx::from_seed([seed, 2, 3, 4], String::from("test_order_crossover_rng"));
+            let mut mom : Vec<usize> = (0..30).collect();
+            let mut dad : Vec<usize> = (0..30).collect();
+            rng_ctx.shuffle(&mut mom);
+            rng_ctx.shuffle(&mut dad);
+
+            let child = OrderCrossover.cross(&mom, &dad, &mut rng_ctx);
+            assert!(is_permutation(&child));
+            // Some of mom's genes are kept in place.
+            assert!(child.iter().zip(mom.iter()).any(|(c, m)| c == m));
+        }
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_partially_mapped_crossover()
+    {
+        ga_test_setup("ga_crossover::test_partially_mapped_crossover");
+
+        let first : Vec<usize> = (0..9).collect();
+        let second = vec![8, 2, 6, 7, 1, 5, 4, 0, 3];
+        assert_eq!(PartiallyMappedCrossover.cross_segment(&first, &second, 3, 7), vec![8, 2, 1, 3, 4, 5, 6, 0, 7]);
+        assert_eq!(PartiallyMappedCrossover.cross_segment(&first, &second, 0, 9), first);
+        assert_eq!(PartiallyMappedCrossover.cross_segment(&first, &second, 2, 2), second);
+
+        for seed in 1..20
+        {
+            let mut rng_ctx = GARandomCtx::from_seed([seed, 2, 3, 4], String::from("test_partially_mapped_crossover_rng"));
+            let mut mom : Vec<usize> = (0..30).collect();
+            let mut dad : Vec<usize> = (0..30).collect();
+            rng_ctx.shuffle(&mut mom);
+            rng_ctx.shuffle(&mut dad);
+
+            let child = PartiallyMappedCrossover.cross(&mom, &dad, &mut rng_ctx);
+            assert!(is_permutation(&child));
+
+            for start in 0..30
+            {
+                for end in start..31
+                {
+                    let child = PartiallyMappedCrossover.cross_segment(&mom, &dad, start, end);
+                    assert!(is_permutation(&child));
+                    assert_eq!(&child[start..end], &mom[start..end]);
+
+                    let child = OrderCrossover.cross_segment(&mom, &dad, start, end);
+                    assert!(is_permutation(&child));
+                    assert_eq!(&child[start..end], &mom[start..end]);
+                }
+            }
+        }
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_permutation_crossover_operators()
+    {
+        ga_test_setup("ga_crossover::test_permutation_crossover_operators");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_permutation_crossover_operators_rng"));
+
+        let operators : Vec<Box<GACrossover<usize>>> = vec![Box::new(OrderCrossover), Box::new(PartiallyMappedCrossover)];
+        let mom : Vec<usize> = (0..10).collect();
+        let dad : Vec<usize> = (0..10).rev().collect();
+        for op in &operators
+        {
+            assert!(is_permutation(&op.cross(&mom, &dad, &mut rng_ctx)));
+        }
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_permutation_crossover_not_a_permutation()
+    {
+        ga_test_setup("ga_crossover::test_permutation_crossover_not_a_permutation");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_permutation_crossover_not_a_permutation_rng"));
+        OrderCrossover.cross(&[0, 1, 1], &[0, 1, 2], &mut rng_ctx);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_permutation_crossover_different_lengths()
+    {
+        ga_test_setup("ga_crossover::test_permutation_crossover_different_lengths");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_permutation_crossover_different_lengths_rng"));
+        PartiallyMappedCrossover.cross(&[0, 1, 2], &[0, 1], &mut rng_ctx);
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright 2016 Revolution Solid & Contributors.
+// author(s): sysnett
+// rust-monster is licensed under a MIT License.
+
+//! GA Diploid Individuals
+//!
+//! Individuals with 2 homologous gene sets and a dominance map. Only the
+//! expressed genes (the phenotype) are evaluated, the recessive ones are
+//! carried along and can resurface through crossover.
+use ::ga::ga_core::GAIndividual;
+use ::ga::ga_crossover::{GAGene, GAVectorCrossover};
+use ::ga::ga_random::GARandomCtx;
+
+use std::any::Any;
+
+/// Diploid Evaluation Context
+///
+/// Objective function over the expressed genes of a `GADiploidIndividual`.
+/// Pass it as the evaluation context.
+pub struct GADiploidObjective<G: GAGene>
+{
+    pub objective: fn(&[G]) -> f32
+}
+
+/// Diploid Individual
+///
+/// Each locus has 2 genes, one per homolog. The dominance map tells which
+/// one is expressed: `true` for the first homolog, `false` for the second.
+#[derive(Clone, PartialEq)]
+pub struct GADiploidIndividual<G: GAGene = bool>
+{
+    first: Vec<G>,
+    second: Vec<G>,
+    dominance: Vec<bool>,
+    crossover: GAVectorCrossover,
+    raw: f32,
+    fitness: f32
+}
+impl<G: GAGene> GADiploidIndividual<G>
+{
+    pub fn new(first: Vec<G>, second: Vec<G>, dominance: Vec<bool>) -> GADiploidIndividual<G>
+    {
+        assert_eq!(first.len(), second.len());
+        assert_eq!(first.len(), dominance.len());
+
+        GADiploidIndividual
+        {
+            first: first,
+            second: second,
+            dominance: dominance,
+            crossover: GAVectorCrossover::default(),
+            raw: 0.0,
+            fitness: 0.0
+        }
+    }
+
+    /// Use `crossover` to recombine the homologs (single point by default).
+    pub fn with_crossover(mut self, crossover: GAVectorCrossover) -> GADiploidIndividual<G>
+    {
+        self.crossover = crossover;
+        self
+    }
+
+    pub fn first(&self) -> &[G] { &self.first }
+    pub fn second(&self) -> &[G] { &self.second }
+    pub fn dominance(&self) -> &[bool] { &self.dominance }
+
+    /// Expressed genes: the dominant gene of each locus.
+    pub fn phenotype(&self) -> Vec<G>
+    {
+        self.dominance.iter().enumerate()
+            .map(|(i, first_dominates)| if *first_dominates { self.first[i] } else { self.second[i] })
+            .collect()
+    }
+}
+impl<G: GAGene + 'static> GAIndividual for GADiploidIndividual<G>
+{
+    /// Each homolog is crossed over with the other parent's counterpart,
+    /// independently. The child inherits this individual's dominance map.
+    ///
+    /// Expects a `GARandomCtx` as context.
+    fn crossover(&self, other: &GADiploidIndividual<G>, ctx: &mut Any) -> Box<GADiploidIndividual<G>>
+    {
+        match ctx.downcast_mut::<GARandomCtx>()
+        {
+            Some(rng_ctx) =>
+            {
+                let first = self.crossover.crossover(&self.first, &other.first, rng_ctx);
+                let second = self.crossover.crossover(&self.second, &other.second, rng_ctx);
+                let dominance = self.dominance[..first.len()].to_vec();
+
+                Box::new(GADiploidIndividual::new(first, second, dominance).with_crossover(self.crossover))
+            },
+            None =>
+            {
+                panic!("Incorrect type passed for context");
+            }
+        }
+    }
+
+    /// Both homologs mutate. Expects a `GARandomCtx` as context.
+    fn mutate(&mut self, probability: f32, ctx: &mut Any)
+    {
+        match ctx.downcast_mut::<GARandomCtx>()
+        {
+            Some(rng_ctx) =>
+            {
+                G::mutate_genes(&mut self.first, probability, rng_ctx);
+                G::mutate_genes(&mut self.second, probability, rng_ctx);
+            },
+            None =>
+            {
+                panic!("Incorrect type passed for context");
+            }
+        }
+    }
+
+    /// Evaluate the phenotype. Expects a `GADiploidObjective` as context.
+    fn evaluate(&mut self, ctx: &mut Any)
+    {
+        match ctx.downcast_mut::<GADiploidObjective<G>>()
+        {
+            Some(objective) =>
+            {
+                self.raw = (objective.objective)(&self.phenotype());
+                self.fitness = self.raw;
+            },
+            None =>
+            {
+                panic!("Incorrect type passed for context");
+            }
+        }
+    }
+
+    fn fitness(&self) -> f32 { self.fitness }
+    fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+    fn raw(&self) -> f32 { self.raw }
+    fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+}
+
+////////////////////////////////////////
+// Tests
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use ::ga::ga_core::GAIndividual;
+    use ::ga::ga_random::GARandomCtx;
+    use ::ga::ga_test::*;
+
+    fn count_ones() -> GADiploidObjective<bool>
+    {
+        GADiploidObjective { objective: |genes: &[bool]| genes.iter().filter(|g| **g).count() as f32 }
+    }
+
+    #[test]
+    fn test_diploid_phenotype()
+    {
+        ga_test_setup("ga_diploid::test_diploid_phenotype");
+
+        let mut ind = GADiploidIndividual::new(vec![true, true, false, false],
+                                               vec![false, false, true, true],
+                                               vec![true, false, true, false]);
+        assert_eq!(ind.phenotype(), vec![true, false, false, true]);
+
+        ind.evaluate(&mut count_ones());
+        assert_eq!(ind.raw(), 2.0);
+
+        // Fully dominant first homolog.
+        let mut ind = GADiploidIndividual::new(vec![true; 4], vec![false; 4], vec![true; 4]);
+        ind.evaluate(&mut count_ones());
+        assert_eq!(ind.raw(), 4.0);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_diploid_crossover()
+    {
+        ga_test_setup("ga_diploid::test_diploid_crossover");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_diploid_crossover_rng"));
+
+        let mom = GADiploidIndividual::new(vec![1.0; 8], vec![3.0; 8], vec![true; 8])
+                      .with_crossover(GAVectorCrossover::Uniform);
+        let dad = GADiploidIndividual::new(vec![2.0; 8], vec![4.0; 8], vec![false; 8]);
+
+        for _ in 0..20
+        {
+            let child = mom.crossover(&dad, &mut rng_ctx);
+            assert_eq!(child.first().len(), 8);
+            assert_eq!(child.second().len(), 8);
+            assert_eq!(child.dominance(), mom.dominance());
+            assert_eq!(child.phenotype(), child.first());
+
+            // Homologs recombine with their counterparts only.
+            assert!(child.first().iter().all(|g| *g == 1.0 || *g == 2.0));
+            assert!(child.second().iter().all(|g| *g == 3.0 || *g == 4.0));
+        }
+
+        ga_test_teardown();
+    }
+}
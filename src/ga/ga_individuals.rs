@@ -0,0 +1,571 @@
+// Copyright 2016 Revolution Solid & Contributors.
+// author(s): sysnett
+// rust-monster is licensed under a MIT License.
+
+//! GA Built-in Individuals
+//!
+//! Ready to use individuals for common encodings, with the standard
+//! operators, so only the objective function needs to be written.
+use ::ga::ga_core::{GADiversity, GAFactory, GAIndividual};
+use ::ga::ga_crossover::{BlxAlphaCrossover, GACrossover, HasGenes};
+use ::ga::ga_population::{GAPopulation, GAPopulationSortOrder};
+use ::ga::ga_random::GARandomCtx;
+
+use std::any::Any;
+use std::f64;
+
+/// Binary Evaluation Context
+///
+/// Objective function over the bits of a `GABinaryIndividual`. Pass it as
+/// the evaluation context.
+pub struct GABinaryObjective
+{
+    pub objective: fn(&[bool]) -> f32
+}
+
+/// Binary String Individual
+///
+/// A fixed length string of bits. Single point crossover and per-bit flip
+/// mutation, both expect a `GARandomCtx` as context (as the GAs pass).
+#[derive(Clone, Debug, PartialEq)]
+pub struct GABinaryIndividual
+{
+    bits: Vec<bool>,
+    raw: f32,
+    fitness: f32
+}
+impl GABinaryIndividual
+{
+    pub fn new(bits: Vec<bool>) -> GABinaryIndividual
+    {
+        GABinaryIndividual
+        {
+            bits: bits,
+            raw: 0.0,
+            fitness: 0.0
+        }
+    }
+
+    pub fn bits(&self) -> &[bool] { &self.bits }
+}
+impl HasGenes<bool> for GABinaryIndividual
+{
+    fn genes(&self) -> &[bool] { &self.bits }
+    fn genes_mut(&mut self) -> &mut [bool] { &mut self.bits }
+    fn from_genes(bits: Vec<bool>) -> GABinaryIndividual
+    {
+        GABinaryIndividual::new(bits)
+    }
+}
+impl GAIndividual for GABinaryIndividual
+{
+    // Single point crossover: the child takes this individual's bits up to
+    // a random cut, and `other`'s after it. Each bit flips with the mutation
+    // probability.
+    ga_genes_operators!();
+
+    /// Evaluate the bits. Expects a `GABinaryObjective` as context.
+    fn evaluate(&mut self, ctx: &mut Any)
+    {
+        match ctx.downcast_mut::<GABinaryObjective>()
+        {
+            Some(objective) =>
+            {
+                self.raw = (objective.objective)(&self.bits);
+                self.fitness = self.raw;
+            },
+            None =>
+            {
+                panic!("Incorrect type passed for context");
+            }
+        }
+    }
+
+    fn fitness(&self) -> f32 { self.fitness }
+    fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+    fn raw(&self) -> f32 { self.raw }
+    fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+}
+
+impl GADiversity for GABinaryIndividual
+{
+    /// Hamming distance.
+    fn distance(&self, other: &GABinaryIndividual) -> f32
+    {
+        self.bits.iter().zip(other.bits.iter()).filter(|&(l, r)| l != r).count() as f32
+    }
+}
+
+/// Binary Individual Factory
+///
+/// Random bit strings of a fixed length.
+pub struct GABinaryFactory
+{
+    length: usize
+}
+impl GABinaryFactory
+{
+    pub fn new(length: usize) -> GABinaryFactory
+    {
+        GABinaryFactory { length: length }
+    }
+}
+impl GAFactory<GABinaryIndividual> for GABinaryFactory
+{
+    fn random_population(&mut self, n: usize, sort_order: GAPopulationSortOrder, rng_ctx: &mut GARandomCtx) -> GAPopulation<GABinaryIndividual>
+    {
+        let inds = (0..n).map(|_| GABinaryIndividual::new(rng_ctx.gen_bits(self.length))).collect();
+        GAPopulation::new(inds, sort_order)
+    }
+}
+
+/// Whether an objective function is minimized or maximized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GAObjectiveDirection
+{
+    Minimize,
+    Maximize
+}
+impl GAObjectiveDirection
+{
+    /// Population sort order that optimizes in this direction.
+    pub fn sort_order(&self) -> GAPopulationSortOrder
+    {
+        match *self
+        {
+            GAObjectiveDirection::Minimize => GAPopulationSortOrder::LowIsBest,
+            GAObjectiveDirection::Maximize => GAPopulationSortOrder::HighIsBest
+        }
+    }
+}
+
+/// Real Vector Evaluation Context
+///
+/// Objective function over the genes of a `GARealVectorIndividual`. Pass
+/// it as the evaluation context.
+pub struct GARealObjective
+{
+    pub objective: fn(&[f64]) -> f32
+}
+
+/// Default standard deviation of the gaussian mutation of
+/// `GARealVectorIndividual`s.
+pub const GA_REAL_VECTOR_STD_DEV : f64 = 1.0;
+
+/// Real Vector Individual
+///
+/// A vector of real genes, for continuous optimization, each within the
+/// bounds of its dimension (unbounded by default). Raw and fitness scores
+/// are the objective value as is; the individual carries whether the
+/// objective is minimized or maximized, populations of it should be sorted
+/// with `direction().sort_order()`, so no sign flipping is needed.
+///
+/// Crossover with a `GACrossover<f64>` operator (BLX-alpha by default) and
+/// gaussian mutation, both expect a `GARandomCtx` as context. Offspring are
+/// clamped to the bounds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GARealVectorIndividual<C: GACrossover<f64> = BlxAlphaCrossover>
+{
+    genes: Vec<f64>,
+    bounds: Vec<(f64, f64)>,
+    direction: GAObjectiveDirection,
+    crossover: C,
+    mutation_std_dev: f64,
+    raw: f32,
+    fitness: f32
+}
+impl GARealVectorIndividual
+{
+    pub fn new(genes: Vec<f64>, direction: GAObjectiveDirection) -> GARealVectorIndividual
+    {
+        let bounds = vec![(f64::NEG_INFINITY, f64::INFINITY); genes.len()];
+        GARealVectorIndividual
+        {
+            genes: genes,
+            bounds: bounds,
+            direction: direction,
+            crossover: BlxAlphaCrossover::default(),
+            mutation_std_dev: GA_REAL_VECTOR_STD_DEV,
+            raw: 0.0,
+            fitness: 0.0
+        }
+    }
+}
+impl<C: GACrossover<f64>> GARealVectorIndividual<C>
+{
+    /// Bound each gene to `[low, high]` of its dimension, clamping the
+    /// current genes.
+    ///
+    /// Panics if there isn't one bound per gene, or if `low > high`.
+    pub fn with_bounds(mut self, bounds: Vec<(f64, f64)>) -> GARealVectorIndividual<C>
+    {
+        assert_eq!(bounds.len(), self.genes.len(), "GARealVectorIndividual: {} bounds for {} genes", bounds.len(), self.genes.len());
+        assert!(bounds.iter().all(|&(low, high)| low <= high), "GARealVectorIndividual: empty bounds");
+        self.bounds = bounds;
+        self.clamp();
+        self
+    }
+
+    /// Use `crossover` to recombine genes.
+    pub fn with_crossover<D: GACrossover<f64>>(self, crossover: D) -> GARealVectorIndividual<D>
+    {
+        GARealVectorIndividual
+        {
+            genes: self.genes,
+            bounds: self.bounds,
+            direction: self.direction,
+            crossover: crossover,
+            mutation_std_dev: self.mutation_std_dev,
+            raw: self.raw,
+            fitness: self.fitness
+        }
+    }
+
+    /// Standard deviation of the gaussian mutation (`GA_REAL_VECTOR_STD_DEV`
+    /// by default).
+    pub fn with_mutation_std_dev(mut self, std_dev: f64) -> GARealVectorIndividual<C>
+    {
+        self.mutation_std_dev = std_dev;
+        self
+    }
+
+    pub fn genes(&self) -> &[f64] { &self.genes }
+    pub fn bounds(&self) -> &[(f64, f64)] { &self.bounds }
+    pub fn direction(&self) -> GAObjectiveDirection { self.direction }
+
+    fn clamp(&mut self)
+    {
+        for (gene, &(low, high)) in self.genes.iter_mut().zip(self.bounds.iter())
+        {
+            *gene = gene.max(low).min(high);
+        }
+    }
+}
+impl<C: GACrossover<f64> + Clone + Default> HasGenes<f64> for GARealVectorIndividual<C>
+{
+    fn genes(&self) -> &[f64] { &self.genes }
+    fn genes_mut(&mut self) -> &mut [f64] { &mut self.genes }
+
+    /// Unbounded, minimizing individual, with the default crossover.
+    fn from_genes(genes: Vec<f64>) -> GARealVectorIndividual<C>
+    {
+        GARealVectorIndividual::new(genes, GAObjectiveDirection::Minimize).with_crossover(C::default())
+    }
+
+    /// Same bounds (truncated to the length of `genes`), direction and
+    /// operators as this individual, genes clamped to the bounds.
+    fn child_with_genes(&self, genes: Vec<f64>) -> GARealVectorIndividual<C>
+    {
+        let mut child = self.clone();
+        child.bounds.truncate(genes.len());
+        child.genes = genes;
+        child.clamp();
+        child
+    }
+
+    fn genes_mutation_std_dev(&self) -> f64 { self.mutation_std_dev }
+}
+impl<C: GACrossover<f64> + Clone + Default> GAIndividual for GARealVectorIndividual<C>
+{
+    /// Recombine with the crossover operator. The child inherits this
+    /// individual's bounds, and has the length of the shortest parent.
+    fn crossover(&self, other: &GARealVectorIndividual<C>, ctx: &mut Any) -> Box<GARealVectorIndividual<C>>
+    {
+        self.crossover_genes_with(other, &self.crossover, ctx)
+    }
+
+    /// Each gene mutates with the given probability, by adding gaussian
+    /// noise, and is clamped back into its bounds.
+    fn mutate(&mut self, probability: f32, ctx: &mut Any)
+    {
+        self.mutate_genes(probability, ctx);
+        self.clamp();
+    }
+
+    /// Evaluate the genes. Expects a `GARealObjective` as context.
+    fn evaluate(&mut self, ctx: &mut Any)
+    {
+        match ctx.downcast_mut::<GARealObjective>()
+        {
+            Some(objective) =>
+            {
+                self.raw = (objective.objective)(&self.genes);
+                self.fitness = self.raw;
+            },
+            None =>
+            {
+                panic!("Incorrect type passed for context");
+            }
+        }
+    }
+
+    fn fitness(&self) -> f32 { self.fitness }
+    fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+    fn raw(&self) -> f32 { self.raw }
+    fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+}
+
+impl<C: GACrossover<f64> + Clone + Default> GADiversity for GARealVectorIndividual<C>
+{
+    /// Euclidean distance.
+    fn distance(&self, other: &GARealVectorIndividual<C>) -> f32
+    {
+        self.genes.iter().zip(other.genes.iter()).map(|(l, r)| (l - r) * (l - r)).sum::<f64>().sqrt() as f32
+    }
+}
+
+/// Real Vector Individual Factory
+///
+/// Vectors of `dim` genes, drawn uniformly in `[low, high)`, which are also
+/// the bounds of the individuals. Populations must be sorted with
+/// `direction.sort_order()`.
+pub struct GARealVectorFactory<C: GACrossover<f64> = BlxAlphaCrossover>
+{
+    dim: usize,
+    low: f64,
+    high: f64,
+    direction: GAObjectiveDirection,
+    crossover: C
+}
+impl GARealVectorFactory
+{
+    pub fn new(dim: usize, low: f64, high: f64, direction: GAObjectiveDirection) -> GARealVectorFactory
+    {
+        assert!(low < high, "GARealVectorFactory: empty range [{}, {})", low, high);
+        GARealVectorFactory { dim: dim, low: low, high: high, direction: direction, crossover: BlxAlphaCrossover::default() }
+    }
+}
+impl<C: GACrossover<f64>> GARealVectorFactory<C>
+{
+    /// Individuals recombine with `crossover`.
+    pub fn with_crossover<D: GACrossover<f64>>(self, crossover: D) -> GARealVectorFactory<D>
+    {
+        GARealVectorFactory { dim: self.dim, low: self.low, high: self.high, direction: self.direction, crossover: crossover }
+    }
+}
+impl<C: GACrossover<f64> + Clone + Default> GAFactory<GARealVectorIndividual<C>> for GARealVectorFactory<C>
+{
+    /// Panics if `sort_order` doesn't optimize in the factory's direction.
+    fn random_population(&mut self, n: usize, sort_order: GAPopulationSortOrder, rng_ctx: &mut GARandomCtx) -> GAPopulation<GARealVectorIndividual<C>>
+    {
+        assert!(sort_order == self.direction.sort_order(), "GARealVectorFactory: sort order doesn't match the objective direction");
+
+        let inds = (0..n).map(|_|
+        {
+            let genes = (0..self.dim).map(|_| rng_ctx.gen_range(self.low, self.high)).collect();
+            GARealVectorIndividual::new(genes, self.direction)
+                .with_bounds(vec![(self.low, self.high); self.dim])
+                .with_crossover(self.crossover.clone())
+        }).collect();
+        GAPopulation::new(inds, sort_order)
+    }
+}
+
+////////////////////////////////////////
+// Tests
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use ::ga::ga_core::{GAFactory, GAIndividual, GeneticAlgorithm};
+    use ::ga::ga_crossover::SimulatedBinaryCrossover;
+    use ::ga::ga_population::GAPopulationSortOrder;
+    use ::ga::ga_random::GARandomCtx;
+    use ::ga::ga_simple::{SimpleGeneticAlgorithm, SimpleGeneticAlgorithmCfg};
+    use ::ga::ga_test::*;
+
+    use std::any::Any;
+
+    fn count_ones() -> GABinaryObjective
+    {
+        GABinaryObjective { objective: |bits: &[bool]| bits.iter().filter(|b| **b).count() as f32 }
+    }
+
+    #[test]
+    fn test_binary_crossover()
+    {
+        ga_test_setup("ga_individuals::test_binary_crossover");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_binary_crossover_rng"));
+
+        let mom = GABinaryIndividual::new(vec![false; 16]);
+        let dad = GABinaryIndividual::new(vec![true; 16]);
+
+        for _ in 0..20
+        {
+            let child = mom.crossover(&dad, &mut rng_ctx);
+            assert_eq!(child.bits().len(), 16);
+
+            // Mom's bits up to the cut, dad's after it.
+            let cut = child.bits().iter().position(|b| *b).unwrap();
+            assert!(cut > 0);
+            assert!(child.bits()[..cut].iter().all(|b| !*b));
+            assert!(child.bits()[cut..].iter().all(|b| *b));
+        }
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_binary_mutate()
+    {
+        ga_test_setup("ga_individuals::test_binary_mutate");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_binary_mutate_rng"));
+
+        let mut ind = GABinaryIndividual::new(vec![false; 2000]);
+        ind.mutate(0.0, &mut rng_ctx);
+        assert!(ind.bits().iter().all(|b| !*b));
+
+        ind.mutate(0.1, &mut rng_ctx);
+        let flipped = ind.bits().iter().filter(|b| **b).count();
+        assert!(flipped > 160 && flipped < 240, "{} bits flipped", flipped);
+
+        ind.evaluate(&mut count_ones());
+        assert_eq!(ind.raw(), flipped as f32);
+        assert_eq!(ind.distance(&GABinaryIndividual::new(vec![false; 2000])), flipped as f32);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_binary_factory()
+    {
+        ga_test_setup("ga_individuals::test_binary_factory");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_binary_factory_rng"));
+
+        let mut factory = GABinaryFactory::new(32);
+        let mut pop = factory.random_population(10, GAPopulationSortOrder::HighIsBest, &mut rng_ctx);
+        assert_eq!(pop.size(), 10);
+        let inds = pop.population();
+        assert!(inds.iter().all(|ind| ind.bits().len() == 32));
+        assert!(inds[0] != inds[1]);
+
+        ga_test_teardown();
+    }
+
+
+    fn shifted_sphere(genes: &[f64]) -> f32
+    {
+        ((genes[0] - 1.0).powi(2) + (genes[1] + 2.0).powi(2)) as f32
+    }
+
+    // Maximal where `shifted_sphere` is minimal, and positive within the
+    // factory bounds: roulette selection only favors the highest scores when
+    // they aren't negative.
+    fn negated_shifted_sphere(genes: &[f64]) -> f32
+    {
+        100.0 - shifted_sphere(genes)
+    }
+
+    #[test]
+    fn test_real_vector_clamping()
+    {
+        ga_test_setup("ga_individuals::test_real_vector_clamping");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_real_vector_clamping_rng"));
+
+        let bounds = vec![(0.0, 1.0), (-2.0, -1.0)];
+        let ind = GARealVectorIndividual::new(vec![3.0, -5.0], GAObjectiveDirection::Minimize).with_bounds(bounds.clone());
+        assert_eq!(ind.genes(), &[1.0, -2.0]);
+        assert_eq!(ind.bounds(), &bounds[..]);
+
+        // Genes sitting on the edges, with a large mutation.
+        let mut on_bound = 0;
+        for _ in 0..100
+        {
+            let mut mutant = ind.clone().with_mutation_std_dev(10.0);
+            mutant.mutate(1.0, &mut rng_ctx);
+            for (g, &(low, high)) in mutant.genes().iter().zip(bounds.iter())
+            {
+                assert!(*g >= low && *g <= high, "{} not in [{}, {}]", g, low, high);
+                if *g == low || *g == high
+                {
+                    on_bound += 1;
+                }
+            }
+        }
+        assert!(on_bound > 100, "{} genes on a bound", on_bound);
+
+        // Offspring of both crossover operators, from parents on opposite edges.
+        let other = GARealVectorIndividual::new(vec![0.0, -1.0], GAObjectiveDirection::Minimize).with_bounds(bounds.clone());
+        let sbx_ind = ind.clone().with_crossover(SimulatedBinaryCrossover { eta: 0.1 });
+        let sbx_other = other.clone().with_crossover(SimulatedBinaryCrossover { eta: 0.1 });
+        for _ in 0..100
+        {
+            let blx_child = ind.crossover(&other, &mut rng_ctx);
+            let sbx_child = sbx_ind.crossover(&sbx_other, &mut rng_ctx);
+            assert_eq!(blx_child.bounds(), &bounds[..]);
+            for child in &[blx_child.genes(), sbx_child.genes()]
+            {
+                assert!(child.iter().zip(bounds.iter()).all(|(g, &(low, high))| *g >= low && *g <= high), "{:?}", child);
+            }
+        }
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_real_vector_bounds_length()
+    {
+        ga_test_setup("ga_individuals::test_real_vector_bounds_length");
+        GARealVectorIndividual::new(vec![0.0, 0.0], GAObjectiveDirection::Minimize).with_bounds(vec![(0.0, 1.0)]);
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_real_vector_factory_sort_order()
+    {
+        ga_test_setup("ga_individuals::test_real_vector_factory_sort_order");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_real_vector_factory_sort_order_rng"));
+        let mut factory = GARealVectorFactory::new(2, -5.0, 5.0, GAObjectiveDirection::Minimize);
+        factory.random_population(10, GAPopulationSortOrder::HighIsBest, &mut rng_ctx);
+        ga_test_teardown();
+    }
+
+    // Best genes found optimizing `objective` in `direction`.
+    fn optimize(objective: fn(&[f64]) -> f32, direction: GAObjectiveDirection) -> Vec<f64>
+    {
+        let mut factory = GARealVectorFactory::new(2, -5.0, 5.0, direction);
+        let mut ctx = GARealObjective { objective: objective };
+        let mut ga : SimpleGeneticAlgorithm<GARealVectorIndividual> =
+            SimpleGeneticAlgorithm::new_with_eval_ctx(SimpleGeneticAlgorithmCfg {
+                                                        d_seed : [1; 4],
+                                                        max_generations: 100,
+                                                        population_size: 40,
+                                                        probability_crossover: 0.5,
+                                                        probability_mutation: 0.2,
+                                                        population_sort_order: direction.sort_order(),
+                                                        elitism: true,
+                                                        ..Default::default()
+                                                      },
+                                                      Some(&mut factory as &mut GAFactory<GARealVectorIndividual>),
+                                                      None,
+                                                      Some(&mut ctx as &mut Any));
+        ga.initialize();
+        while !ga.done()
+        {
+            ga.step();
+        }
+        ga.population().best_by_raw_score().genes().to_vec()
+    }
+
+    #[test]
+    fn test_real_vector_direction()
+    {
+        ga_test_setup("ga_individuals::test_real_vector_direction");
+
+        assert!(GAObjectiveDirection::Minimize.sort_order() == GAPopulationSortOrder::LowIsBest);
+        assert!(GAObjectiveDirection::Maximize.sort_order() == GAPopulationSortOrder::HighIsBest);
+
+        let minimum = optimize(shifted_sphere, GAObjectiveDirection::Minimize);
+        let maximum = optimize(negated_shifted_sphere, GAObjectiveDirection::Maximize);
+
+        for best in &[minimum, maximum]
+        {
+            assert!((best[0] - 1.0).abs() < 0.5, "{:?}", best);
+            assert!((best[1] + 2.0).abs() < 0.5, "{:?}", best);
+        }
+
+        ga_test_teardown();
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2016 Revolution Solid & Contributors.
+// author(s): sysnett
+// rust-monster is licensed under a MIT License.
+
+//! GA Mutation Operators
+//!
+//! Reusable mutation operators for individuals whose genome is a vector
+//! of genes, and `GAMutation` operators for permutations.
+use ::ga::ga_random::GARandomCtx;
+
+use rand::distributions::normal::StandardNormal;
+
+/// Gaussian mutation.
+///
+/// Each gene mutates with the given probability, by adding normally
+/// distributed noise with mean 0 and the given standard deviation.
+pub fn gaussian_mutate(genes: &mut [f32], probability: f32, std_dev: f32, rng_ctx: &mut GARandomCtx)
+{
+    for gene in genes.iter_mut()
+    {
+        if rng_ctx.test_value(probability)
+        {
+            let StandardNormal(noise) = rng_ctx.gen::<StandardNormal>();
+            *gene += std_dev * noise as f32;
+        }
+    }
+}
+
+/// Gaussian mutation of `f64` genes, see `gaussian_mutate`.
+pub fn gaussian_mutate_f64(genes: &mut [f64], probability: f32, std_dev: f64, rng_ctx: &mut GARandomCtx)
+{
+    for gene in genes.iter_mut()
+    {
+        if rng_ctx.test_value(probability)
+        {
+            *gene += rng_ctx.gen_gaussian(0.0, std_dev);
+        }
+    }
+}
+
+/// Bit flip mutation.
+///
+/// Each bit flips with the given probability.
+pub fn flip_mutate(genes: &mut [bool], probability: f32, rng_ctx: &mut GARandomCtx)
+{
+    for gene in genes.iter_mut()
+    {
+        if rng_ctx.test_value(probability)
+        {
+            *gene = !*gene;
+        }
+    }
+}
+
+/// Mutation operator over permutations of `0..n`.
+///
+/// The genome mutates as a whole with the given probability, and stays a
+/// permutation.
+pub trait GAMutation
+{
+    fn mutate(&self, genes: &mut [usize], probability: f32, rng_ctx: &mut GARandomCtx);
+}
+
+/// Swap mutation.
+///
+/// Swaps the genes at 2 distinct random positions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SwapMutation;
+
+impl GAMutation for SwapMutation
+{
+    fn mutate(&self, genes: &mut [usize], probability: f32, rng_ctx: &mut GARandomCtx)
+    {
+        if genes.len() < 2 || !rng_ctx.test_value(probability)
+        {
+            return;
+        }
+        let p1 = rng_ctx.gen_range(0, genes.len());
+        let mut p2 = p1;
+        while p1 == p2
+        {
+            p2 = rng_ctx.gen_range(0, genes.len());
+        }
+        genes.swap(p1, p2);
+    }
+}
+
+/// Inversion mutation.
+///
+/// Reverses a random sub-slice (of at least 2 genes).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InversionMutation;
+
+impl GAMutation for InversionMutation
+{
+    fn mutate(&self, genes: &mut [usize], probability: f32, rng_ctx: &mut GARandomCtx)
+    {
+        if genes.len() < 2 || !rng_ctx.test_value(probability)
+        {
+            return;
+        }
+        let (start, end) = random_sub_slice(genes.len(), rng_ctx);
+        genes[start..end].reverse();
+    }
+}
+
+/// Scramble mutation.
+///
+/// Shuffles a random sub-slice (of at least 2 genes).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScrambleMutation;
+
+impl GAMutation for ScrambleMutation
+{
+    fn mutate(&self, genes: &mut [usize], probability: f32, rng_ctx: &mut GARandomCtx)
+    {
+        if genes.len() < 2 || !rng_ctx.test_value(probability)
+        {
+            return;
+        }
+        let (start, end) = random_sub_slice(genes.len(), rng_ctx);
+        rng_ctx.shuffle_range(genes, start, end);
+    }
+}
+
+// Random sub-slice [start, end) of at least 2 genes of a genome of length
+// n >= 2.
+fn random_sub_slice(n: usize, rng_ctx: &mut GARandomCtx) -> (usize, usize)
+{
+    let mut ends = rng_ctx.sample_indices(n, 2);
+    ends.sort();
+    (ends[0], ends[1] + 1)
+}
+
+////////////////////////////////////////
+// Tests
+#[cfg(test)]
+mod test
+{
+    use super::*;
+    use ::ga::ga_random::GARandomCtx;
+    use ::ga::ga_test::*;
+
+    #[test]
+    fn test_gaussian_mutate()
+    {
+        ga_test_setup("ga_mutation::test_gaussian_mutate");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_gaussian_mutate_rng"));
+
+        let mut genes = vec![0.0; 1000];
+        gaussian_mutate(&mut genes, 1.0, 0.5, &mut rng_ctx);
+
+        let mean = genes.iter().fold(0.0, |sum, g| sum + g) / genes.len() as f32;
+        let var = genes.iter().fold(0.0, |var, g| var + (g - mean).powi(2)) / (genes.len()-1) as f32;
+        debug!("{:?} {:?}", mean, var);
+        assert!(mean.abs() < 0.1);
+        assert!((var.sqrt() - 0.5).abs() < 0.1);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_flip_mutate()
+    {
+        ga_test_setup("ga_mutation::test_flip_mutate");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_flip_mutate_rng"));
+
+        let mut genes = vec![false; 1000];
+        flip_mutate(&mut genes, 0.0, &mut rng_ctx);
+        assert!(genes.iter().all(|g| !*g));
+
+        flip_mutate(&mut genes, 0.25, &mut rng_ctx);
+        let flipped = genes.iter().filter(|g| **g).count();
+        debug!("{:?}", flipped);
+        assert!(flipped > 200 && flipped < 300);
+
+        ga_test_teardown();
+    }
+
+    fn is_permutation(genome: &[usize]) -> bool
+    {
+        let mut sorted = genome.to_vec();
+        sorted.sort();
+        sorted == (0..genome.len()).collect::<Vec<usize>>()
+    }
+
+    fn check_permutation_mutation<M: GAMutation>(op: M, name: &str)
+    {
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], format!("{}_rng", name));
+
+        let original : Vec<usize> = (0..20).collect();
+        let mut genes = original.clone();
+        for _ in 0..100
+        {
+            op.mutate(&mut genes, 0.0, &mut rng_ctx);
+        }
+        assert_eq!(genes, original);
+
+        let mut changed = 0;
+        for _ in 0..100
+        {
+            let before = genes.clone();
+            op.mutate(&mut genes, 1.0, &mut rng_ctx);
+            assert!(is_permutation(&genes), "{:?}", genes);
+            if genes != before
+            {
+                changed += 1;
+            }
+        }
+        assert!(changed > 50, "{} changed {} times", name, changed);
+
+        // Too short to mutate.
+        let mut single = vec![0];
+        op.mutate(&mut single, 1.0, &mut rng_ctx);
+        assert_eq!(single, vec![0]);
+    }
+
+    #[test]
+    fn test_permutation_mutations()
+    {
+        ga_test_setup("ga_mutation::test_permutation_mutations");
+        check_permutation_mutation(SwapMutation, "swap_mutation");
+        check_permutation_mutation(InversionMutation, "inversion_mutation");
+        check_permutation_mutation(ScrambleMutation, "scramble_mutation");
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_swap_mutation()
+    {
+        ga_test_setup("ga_mutation::test_swap_mutation");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_swap_mutation_rng"));
+
+        let original : Vec<usize> = (0..10).collect();
+        let mut genes = original.clone();
+        SwapMutation.mutate(&mut genes, 1.0, &mut rng_ctx);
+        let moved : Vec<usize> = (0..10).filter(|i| genes[*i] != original[*i]).collect();
+        assert_eq!(moved.len(), 2);
+        assert_eq!(genes[moved[0]], original[moved[1]]);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_inversion_mutation()
+    {
+        ga_test_setup("ga_mutation::test_inversion_mutation");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_inversion_mutation_rng"));
+
+        let original : Vec<usize> = (0..10).collect();
+        let mut genes = original.clone();
+        InversionMutation.mutate(&mut genes, 1.0, &mut rng_ctx);
+        // A single decreasing run between the untouched ends.
+        let start = (0..10).position(|i| genes[i] != i).unwrap();
+        let end = 10 - (0..10).rev().position(|i| genes[i] != i).unwrap();
+        let mut reversed = genes[start..end].to_vec();
+        reversed.reverse();
+        assert_eq!(reversed, original[start..end].to_vec());
+
+        ga_test_teardown();
+    }
+}
@@ -31,18 +31,105 @@
 //! ```
 //!
 use rand::{Rng, Rand, SeedableRng, XorShiftRng};
+use rand::distributions::normal::{Normal, StandardNormal};
+use rand::distributions::Sample;
 use rand::distributions::range::SampleRange;
 
+use std::cmp;
 use std::fmt;
+use std::ops::Range;
 
 pub type GASeed = [u32; 4];
+
+// State words of `XorShiftRng::new_unseeded()`.
+const GA_XORSHIFT_UNSEEDED : GASeed = [0x193a6754, 0xa8a7d469, 0x97830e05, 0x113ba7bb];
+
+// `GARandomCtx::jump()` polynomial: x^(2^64 - 4) modulo the characteristic
+// polynomial of the xorshift128 transition, bit i being the coefficient of
+// x^i. The jump draws 4 values first, so it advances 2^64 outputs in total.
+const GA_XORSHIFT_JUMP : [u32; 4] = [0x328bcc71, 0x4a827b8c, 0x2f603c29, 0xcd8cd644];
+
+// XorShiftRng that counts its outputs. XorShiftRng only implements
+// `next_u32`, every other draw is built out of them, so the state words it
+// started from (its origin) and the count are its whole state.
+struct GACountingRng
+{
+    rng: XorShiftRng,
+    origin: GASeed,
+    draws: u64
+}
+impl GACountingRng
+{
+    fn new(origin: GASeed) -> GACountingRng
+    {
+        GACountingRng { rng: SeedableRng::from_seed(origin), origin: origin, draws: 0 }
+    }
+
+    // Move the generator to the state `poly(M) s`, where M is the xorshift128
+    // transition and s the current state.
+    fn jump(&mut self, poly: [u32; 4])
+    {
+        // XorShiftRng's state is the last 4 values it generated.
+        let mut state = [0; 4];
+        for word in &mut state
+        {
+            *word = self.next_u32();
+        }
+
+        let mut jumped = [0; 4];
+        for i in 0..128
+        {
+            if poly[i / 32] & (1 << (i % 32)) != 0
+            {
+                for (j, word) in jumped.iter_mut().enumerate()
+                {
+                    *word ^= state[j];
+                }
+            }
+            state = xorshift_step(state);
+        }
+        *self = GACountingRng::new(jumped);
+    }
+}
+impl Rng for GACountingRng
+{
+    fn next_u32(&mut self) -> u32
+    {
+        self.draws += 1;
+        self.rng.next_u32()
+    }
+}
+
+// One xorshift128 transition, the same as `XorShiftRng::next_u32`'s.
+fn xorshift_step(state: [u32; 4]) -> [u32; 4]
+{
+    let [x, y, z, w] = state;
+    let t = x ^ (x << 11);
+    [y, z, w, w ^ (w >> 19) ^ (t ^ (t >> 8))]
+}
+
+/// Snapshot of a `GARandomCtx`, to save and restore its stream exactly.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GARandomState
+{
+    pub seed: GASeed,
+    pub seeded: bool,
+    // Generator state `draws` counts from: the seed, unless the context
+    // jumped.
+    pub origin: GASeed,
+    // `u32` outputs drawn from the generator since `origin`.
+    pub draws: u64,
+    pub values_generated: u64
+}
+
 pub struct GARandomCtx
 {
     seed: GASeed,
-    rng:  XorShiftRng,
+    rng:  GACountingRng,
     name: String,
     seeded: bool,
-    values_generated: u32
+    values_generated: u64
 }
 
 impl GARandomCtx
@@ -50,11 +137,10 @@ impl GARandomCtx
 // Constructors 
     pub fn new_unseeded(name: String) -> GARandomCtx
     {
-        let std_rng = XorShiftRng::new_unseeded();
         GARandomCtx
         {
             seed: [0; 4],
-            rng: std_rng,
+            rng: GACountingRng::new(GA_XORSHIFT_UNSEEDED),
             name: name,
             seeded: false,
             values_generated: 0
@@ -63,17 +149,49 @@ impl GARandomCtx
 
     pub fn from_seed(seed: GASeed, name: String) -> GARandomCtx
     {
-        let std_rng = SeedableRng::from_seed(seed); 
         GARandomCtx
         {
             seed: seed,
-            rng:  std_rng,
+            rng:  GACountingRng::new(seed),
             name: name,
             seeded: true,
             values_generated: 0
         }
     }
 
+    /// Rebuild a context from a `state` snapshot. It generates the same
+    /// values the snapshotted context would have.
+    pub fn from_state(state: GARandomState, name: String) -> GARandomCtx
+    {
+        let mut ctx = if state.seeded
+            {
+                GARandomCtx::from_seed(state.seed, name)
+            }
+            else
+            {
+                GARandomCtx::new_unseeded(name)
+            };
+        ctx.rng = GACountingRng::new(state.origin);
+        for _ in 0..state.draws
+        {
+            ctx.rng.next_u32();
+        }
+        ctx.values_generated = state.values_generated;
+        ctx
+    }
+
+    pub fn state(&self) -> GARandomState
+    {
+        GARandomState
+        {
+            seed: self.seed,
+            seeded: self.seeded,
+            origin: self.rng.origin,
+            draws: self.rng.draws,
+            values_generated: self.values_generated
+        }
+    }
+
 // Random Values - Subset of the RNG Trait
     pub fn gen<T: Rand>(&mut self) -> T where Self: Sized
     {
@@ -87,20 +205,292 @@ impl GARandomCtx
         self.rng.gen_range(low, high)
     }
 
+    /// Draw a value from a `rand` distribution (`Normal`, `Exp`, `Range`, ...).
+    pub fn sample_dist<D: Sample<T>, T>(&mut self, dist: &mut D) -> T
+    {
+        self.values_generated += 1;
+        dist.sample(&mut self.rng)
+    }
+
+    /// Draw from a normal distribution of mean `mean` and standard deviation
+    /// `std_dev`. Counts as one generated value.
+    ///
+    /// Panics if `std_dev` is negative.
+    pub fn gen_gaussian(&mut self, mean: f64, std_dev: f64) -> f64
+    {
+        self.sample_dist(&mut Normal::new(mean, std_dev))
+    }
+
+    /// `f32` version of `gen_gaussian()`.
+    pub fn gen_gaussian_f32(&mut self, mean: f32, std_dev: f32) -> f32
+    {
+        self.gen_gaussian(mean as f64, std_dev as f64) as f32
+    }
+
+    /// Generate a `usize` in `[low, high)` (upper bound excluded).
+    ///
+    /// Panics if the range is empty (`low >= high`).
+    pub fn gen_usize(&mut self, low: usize, high: usize) -> usize
+    {
+        assert!(low < high, "gen_usize: empty range [{}, {})", low, high);
+        self.gen_range(low, high)
+    }
+
+    /// Generate a `usize` in `[low, high]` (upper bound included).
+    ///
+    /// Panics if the range is empty (`low > high`).
+    pub fn gen_usize_inclusive(&mut self, low: usize, high: usize) -> usize
+    {
+        assert!(low <= high, "gen_usize_inclusive: empty range [{}, {}]", low, high);
+        if high == usize::MAX
+        {
+            // high+1 would overflow.
+            if low == 0
+            {
+                return self.gen::<usize>();
+            }
+            return self.gen_range(low - 1, high) + 1;
+        }
+        self.gen_range(low, high + 1)
+    }
+
+    /// Generate an index in the half-open `range`.
+    ///
+    /// Unlike `gen_range`, an empty range doesn't panic, `range.start` is
+    /// returned instead.
+    pub fn gen_in(&mut self, range: Range<usize>) -> usize
+    {
+        if range.start >= range.end
+        {
+            return range.start;
+        }
+        self.gen_range(range.start, range.end)
+    }
+
     pub fn next_u32(&mut self) -> u32 { self.gen::<u32>() }
     pub fn next_u64(&mut self) -> u64 { self.gen::<u64>() }
     pub fn next_f32(&mut self) -> f32 { self.gen::<f32>() }
     pub fn next_f64(&mut self) -> f64 { self.gen::<f64>() }
 
+    /// Fair coin flip: the top bit of a single `u32` draw.
+    pub fn coin(&mut self) -> bool
+    {
+        self.next_u32() >> 31 == 1
+    }
+
+    /// True with probability `numerator/denominator`, for rational
+    /// probabilities (e.g. 1 in 1000). Compares an unbiased integer draw in
+    /// `[0, denominator)` to `numerator`, so there's no float rounding.
+    ///
+    /// Panics if `denominator` is 0 or `numerator > denominator`.
+    pub fn gen_ratio(&mut self, numerator: u32, denominator: u32) -> bool
+    {
+        assert!(denominator > 0 && numerator <= denominator,
+                "gen_ratio: invalid ratio {}/{}", numerator, denominator);
+        self.gen_range(0, denominator) < numerator
+    }
+
+    /// Generate `n` random bits.
+    ///
+    /// Bits are unpacked from `u32` draws, so only `ceil(n/32)` values are
+    /// generated.
+    pub fn gen_bits(&mut self, n: usize) -> Vec<bool>
+    {
+        let mut bits = Vec::with_capacity(n);
+        while bits.len() < n
+        {
+            let word = self.next_u32();
+            let to_take = cmp::min(32, n - bits.len());
+            for b in 0..to_take
+            {
+                bits.push(word & (1 << b) != 0);
+            }
+        }
+        bits
+    }
+
+    /// Generate a random direction: a unit vector of `dim` components,
+    /// uniformly distributed on the sphere.
+    ///
+    /// Components are drawn from a standard normal and then normalized.
+    pub fn gen_unit_vector(&mut self, dim: usize) -> Vec<f32>
+    {
+        if dim == 0
+        {
+            return vec![];
+        }
+
+        loop
+        {
+            let v : Vec<f64> = (0..dim).map(|_| { let StandardNormal(x) = self.gen::<StandardNormal>(); x }).collect();
+            let norm = v.iter().map(|x| x*x).sum::<f64>().sqrt();
+            // All zeroes can't be normalized, draw again.
+            if norm > 0.0
+            {
+                return v.iter().map(|x| (x / norm) as f32).collect();
+            }
+        }
+    }
+
+    /// Generate a uniformly distributed point of the probability simplex:
+    /// `dim` non-negative components that sum to 1 (flat Dirichlet).
+    ///
+    /// Components are exponentially distributed draws, normalized.
+    pub fn gen_simplex_point(&mut self, dim: usize) -> Vec<f32>
+    {
+        // 1-u is in (0, 1], so the logarithm is finite.
+        let v : Vec<f64> = (0..dim).map(|_| -(1.0 - self.gen::<f64>()).ln()).collect();
+        let sum : f64 = v.iter().sum();
+        if sum > 0.0
+        {
+            v.iter().map(|x| (x / sum) as f32).collect()
+        }
+        else
+        {
+            vec![1.0 / dim as f32; dim]
+        }
+    }
+
+    /// Generate a point of the box given by per-dimension bounds: one
+    /// uniform sample in `[lows[i], highs[i])` per dimension. Equal bounds
+    /// give that value.
+    ///
+    /// Panics if the bounds have different lengths or `lows[i] > highs[i]`.
+    pub fn gen_range_vec(&mut self, lows: &[f32], highs: &[f32]) -> Vec<f32>
+    {
+        assert_eq!(lows.len(), highs.len(), "gen_range_vec: {} lows and {} highs", lows.len(), highs.len());
+        lows.iter().zip(highs.iter()).map(|(low, high)|
+        {
+            assert!(low <= high, "gen_range_vec: empty range [{}, {})", low, high);
+            if low == high
+            {
+                return *low;
+            }
+            self.gen_range(*low, *high)
+        }).collect()
+    }
+
+    /// Shuffle `values` in place (Fisher-Yates).
+    ///
+    /// Slices with less than 2 elements are left untouched.
     pub fn shuffle<T>(&mut self, values: &mut [T]) where Self: Sized, T: Copy
     {
-        for i in 0..values.len()-2
+        let len = values.len();
+        if len < 2
+        {
+            return;
+        }
+
+        for i in 0..len-1
+        {
+            let j = self.gen_range(i, len);
+            values.swap(i, j);
+        }
+    }
+
+    /// Pick one of `items` at random, each with probability proportional to
+    /// its weight in `weights`.
+    ///
+    /// Panics if `items` and `weights` have different lengths, if a weight
+    /// is negative or if weights don't sum to more than 0.
+    pub fn choose_weighted<'a, T>(&mut self, items: &'a [T], weights: &[f32]) -> &'a T
+    {
+        assert_eq!(items.len(), weights.len(), "choose_weighted: {} items but {} weights", items.len(), weights.len());
+        assert!(weights.iter().all(|w| *w >= 0.0), "choose_weighted: negative weight");
+        let total : f32 = weights.iter().sum();
+        assert!(total > 0.0, "choose_weighted: weights must sum to more than 0");
+
+        let target = self.gen::<f32>() * total;
+        let mut cumulative = 0.0;
+        let mut chosen = 0;
+        for (i, w) in weights.iter().enumerate()
+        {
+            if *w == 0.0
+            {
+                continue;
+            }
+            // Rounding may leave target past the last cumulative weight, the
+            // last item with weight is chosen then.
+            chosen = i;
+            cumulative += *w;
+            if target < cumulative
+            {
+                break;
+            }
+        }
+        &items[chosen]
+    }
+
+    /// Sample `k` distinct indices from `0..n`, in random order (partial
+    /// Fisher-Yates).
+    ///
+    /// Panics if `k > n`.
+    pub fn sample_indices(&mut self, n: usize, k: usize) -> Vec<usize>
+    {
+        assert!(k <= n, "sample_indices: can't sample {} distinct indices out of {}", k, n);
+
+        let mut indices : Vec<usize> = (0..n).collect();
+        for i in 0..k
         {
-            let j = self.gen_range(i, values.len());
-            let t = values[i];
-            values[i] = values[j];
-            values[j] = t;
+            let j = self.gen_range(i, n);
+            indices.swap(i, j);
         }
+        indices.truncate(k);
+        indices
+    }
+
+    /// Shuffle `values[start..end]` in place, leaving the rest of `values`
+    /// untouched.
+    ///
+    /// Panics if the range is out of bounds or `start > end`.
+    pub fn shuffle_range<T: Copy>(&mut self, values: &mut [T], start: usize, end: usize)
+    {
+        assert!(start <= end && end <= values.len(),
+                "shuffle_range: range [{}, {}) out of bounds for length {}", start, end, values.len());
+        self.shuffle(&mut values[start..end]);
+    }
+
+    /// Advance the generator by `n` outputs, as if `n` `u32` values had been
+    /// generated and thrown away.
+    pub fn discard(&mut self, n: u64)
+    {
+        for _ in 0..n
+        {
+            self.rng.next_u32();
+        }
+        self.values_generated += n;
+    }
+
+    /// Jump ahead 2^64 outputs, as a (huge) `discard` would, in 128
+    /// steps of the generator.
+    ///
+    /// To split one seed into non-overlapping streams, e.g. one per thread,
+    /// seed all the contexts the same and have the `i`th one jump `i` times.
+    /// Doesn't count towards `values_generated`.
+    pub fn jump(&mut self)
+    {
+        self.rng.jump(GA_XORSHIFT_JUMP);
+    }
+
+    /// Crude estimate of the entropy of the generator's output, in bits per
+    /// byte (0 to 8).
+    ///
+    /// Draws `samples` `u32` values (advancing the generator) and computes the
+    /// Shannon entropy of their bytes. A healthy generator gets close to 8, a
+    /// degenerate one close to 0. Needs a few thousand samples to be
+    /// meaningful.
+    pub fn entropy_estimate(&mut self, samples: usize) -> f32
+    {
+        let mut bytes = Vec::with_capacity(samples * 4);
+        for _ in 0..samples
+        {
+            let value = self.next_u32();
+            for b in 0..4
+            {
+                bytes.push((value >> (8 * b)) as u8);
+            }
+        }
+        byte_entropy(&bytes)
     }
 
 // Random Values - GARandomCtx functions
@@ -123,15 +513,37 @@ impl GARandomCtx
         self.values_generated = 0;
         if self.seeded
         {
-            self.rng.reseed(self.seed);
+            self.rng = GACountingRng::new(self.seed);
         }
         else
         {
-            self.rng = XorShiftRng::new_unseeded(); 
+            self.rng = GACountingRng::new(GA_XORSHIFT_UNSEEDED);
         }
     }
 }
 
+// Shannon entropy of a byte stream, in bits per byte.
+fn byte_entropy(bytes: &[u8]) -> f32
+{
+    if bytes.is_empty()
+    {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for b in bytes
+    {
+        counts[*b as usize] += 1;
+    }
+
+    let total = bytes.len() as f32;
+    counts.iter().filter(|c| **c > 0).map(|c|
+    {
+        let p = *c as f32 / total;
+        -p * p.log2()
+    }).sum()
+}
+
 impl fmt::Debug for GARandomCtx
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
@@ -158,7 +570,9 @@ impl fmt::Debug for GARandomCtx
 #[cfg(test)]
 mod test
 {
-    use super::{GASeed, GARandomCtx};
+    use super::{byte_entropy, GASeed, GARandomCtx, GARandomState};
+    use rand::Rng;
+    use rand::distributions::{Normal, Range};
     use ::ga::ga_test::{ga_test_setup, ga_test_teardown};
 
     #[test]
@@ -226,4 +640,545 @@ mod test
         assert_eq!(ga_ctx.gen::<f32>(), ga_ctx_2.gen::<f32>());
         ga_test_teardown();
     }
+
+    #[test]
+    fn gen_bits()
+    {
+        ga_test_setup("ga_random::gen_bits");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+
+        let bits = ga_ctx.gen_bits(10000);
+        assert_eq!(bits.len(), 10000);
+        assert_eq!(bits, ga_ctx_2.gen_bits(10000));
+
+        // 10000 bits need 313 u32 draws.
+        assert_eq!(ga_ctx.values_generated, 313);
+
+        let true_fraction = bits.iter().filter(|b| **b).count() as f32 / bits.len() as f32;
+        debug!("{:?}", true_fraction);
+        assert!((true_fraction - 0.5).abs() < 0.02);
+
+        assert_eq!(ga_ctx.gen_bits(0).len(), 0);
+        assert_eq!(ga_ctx.gen_bits(33).len(), 33);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn discard()
+    {
+        ga_test_setup("ga_random::discard");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+
+        for _ in 0..1000
+        {
+            ga_ctx.gen::<u32>();
+        }
+        ga_ctx_2.discard(1000);
+        assert_eq!(ga_ctx.values_generated, ga_ctx_2.values_generated);
+
+        assert_eq!(ga_ctx.gen::<u32>(), ga_ctx_2.gen::<u32>());
+        assert_eq!(ga_ctx.gen::<f32>(), ga_ctx_2.gen::<f32>());
+        debug!("{:?}", ga_ctx_2);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn gen_unit_vector()
+    {
+        ga_test_setup("ga_random::gen_unit_vector");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+
+        for dim in 1..20
+        {
+            let v = ga_ctx.gen_unit_vector(dim);
+            assert_eq!(v.len(), dim);
+
+            let norm = v.iter().map(|x| x*x).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-5);
+
+            assert_eq!(v, ga_ctx_2.gen_unit_vector(dim));
+        }
+
+        assert_eq!(ga_ctx.gen_unit_vector(0).len(), 0);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn gen_in()
+    {
+        ga_test_setup("ga_random::gen_in");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+
+        for _ in 0..1000
+        {
+            let i = ga_ctx.gen_in(3..7);
+            assert!(i >= 3 && i < 7);
+        }
+        assert_eq!(ga_ctx.gen_in(5..6), 5);
+
+        // Empty ranges
+        assert_eq!(ga_ctx.gen_in(0..0), 0);
+        assert_eq!(ga_ctx.gen_in(4..4), 4);
+        assert_eq!(ga_ctx.gen_in(4..2), 4);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn gen_simplex_point()
+    {
+        ga_test_setup("ga_random::gen_simplex_point");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+
+        for dim in 1..20
+        {
+            let p = ga_ctx.gen_simplex_point(dim);
+            assert_eq!(p.len(), dim);
+            assert!(p.iter().all(|x| *x >= 0.0));
+
+            let sum : f32 = p.iter().sum();
+            assert!((sum - 1.0).abs() < 1e-5);
+
+            assert_eq!(p, ga_ctx_2.gen_simplex_point(dim));
+        }
+
+        assert_eq!(ga_ctx.gen_simplex_point(0).len(), 0);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn gen_range_vec()
+    {
+        ga_test_setup("ga_random::gen_range_vec");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+
+        let lows = [-1.0, 0.0, 100.0, 3.0];
+        let highs = [1.0, 0.001, 200.0, 3.0];
+        for _ in 0..1000
+        {
+            let v = ga_ctx.gen_range_vec(&lows, &highs);
+            assert_eq!(v.len(), 4);
+            for (i, x) in v.iter().enumerate()
+            {
+                assert!(*x >= lows[i] && (*x < highs[i] || lows[i] == highs[i]), "{} not in [{}, {})", x, lows[i], highs[i]);
+            }
+            assert_eq!(v, ga_ctx_2.gen_range_vec(&lows, &highs));
+        }
+
+        assert_eq!(ga_ctx.gen_range_vec(&[], &[]).len(), 0);
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn gen_range_vec_length_mismatch()
+    {
+        ga_test_setup("ga_random::gen_range_vec_length_mismatch");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        ga_ctx.gen_range_vec(&[0.0, 0.0], &[1.0]);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn gen_usize()
+    {
+        ga_test_setup("ga_random::gen_usize");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+
+        let mut seen_exclusive = vec![false; 4];
+        let mut seen_inclusive = vec![false; 4];
+        for _ in 0..1000
+        {
+            seen_exclusive[ga_ctx.gen_usize(0, 3)] = true;
+            seen_inclusive[ga_ctx.gen_usize_inclusive(0, 3)] = true;
+        }
+        assert_eq!(seen_exclusive, vec![true, true, true, false]);
+        assert_eq!(seen_inclusive, vec![true, true, true, true]);
+
+        assert_eq!(ga_ctx.gen_usize(7, 8), 7);
+        assert_eq!(ga_ctx.gen_usize_inclusive(7, 7), 7);
+        assert_eq!(ga_ctx.gen_usize_inclusive(usize::MAX, usize::MAX), usize::MAX);
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn gen_usize_empty()
+    {
+        ga_test_setup("ga_random::gen_usize_empty");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        ga_ctx.gen_usize(3, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn gen_usize_inclusive_empty()
+    {
+        ga_test_setup("ga_random::gen_usize_inclusive_empty");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        ga_ctx.gen_usize_inclusive(4, 3);
+    }
+
+    #[test]
+    fn entropy_estimate()
+    {
+        ga_test_setup("ga_random::entropy_estimate");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+
+        let entropy = ga_ctx.entropy_estimate(10000);
+        debug!("{:?}", entropy);
+        assert!(entropy > 7.9 && entropy <= 8.0);
+        assert_eq!(ga_ctx.values_generated, 10000);
+
+        // Degenerate streams. (An all-zero seed, which would make XorShift
+        // output only zeroes, is rejected by `from_seed`).
+        assert_eq!(byte_entropy(&[0; 4000]), 0.0);
+        assert!((byte_entropy(&[0, 1, 0, 1]) - 1.0).abs() < 1e-6);
+        assert_eq!(byte_entropy(&[]), 0.0);
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn all_zero_seed()
+    {
+        ga_test_setup("ga_random::all_zero_seed");
+        GARandomCtx::from_seed([0; 4], String::from("TestRandomCtx"));
+    }
+
+    #[test]
+    fn shuffle()
+    {
+        ga_test_setup("ga_random::shuffle");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+
+        let mut empty : [usize; 0] = [];
+        ga_ctx.shuffle(&mut empty);
+
+        let mut one = [42];
+        ga_ctx.shuffle(&mut one);
+        assert_eq!(one, [42]);
+        assert_eq!(ga_ctx.values_generated, 0);
+
+        let mut values : Vec<usize> = (0..10).collect();
+        ga_ctx.shuffle(&mut values);
+        debug!("{:?}", values);
+        assert_eq!(values, vec![1, 6, 4, 7, 5, 8, 2, 9, 3, 0]);
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn sample_dist()
+    {
+        ga_test_setup("ga_random::sample_dist");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+
+        let mut range = Range::new(-5.0, 5.0);
+        let values : Vec<f64> = (0..100).map(|_| ga_ctx.sample_dist(&mut range)).collect();
+        assert!(values.iter().all(|v| *v >= -5.0 && *v < 5.0));
+        assert_eq!(ga_ctx.values_generated, 100);
+
+        let values_2 : Vec<f64> = (0..100).map(|_| ga_ctx_2.sample_dist(&mut range)).collect();
+        assert_eq!(values, values_2);
+
+        let mut normal = Normal::new(10.0, 0.1);
+        let x = ga_ctx.sample_dist(&mut normal);
+        assert!((x - 10.0).abs() < 1.0);
+        assert_eq!(x, ga_ctx_2.sample_dist(&mut normal));
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn shuffle_range()
+    {
+        ga_test_setup("ga_random::shuffle_range");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+
+        let original : Vec<u32> = (0..20).collect();
+        let mut values = original.clone();
+        ga_ctx.shuffle_range(&mut values, 5, 15);
+
+        assert_eq!(&values[..5], &original[..5]);
+        assert_eq!(&values[15..], &original[15..]);
+        assert!(values[5..15] != original[5..15]);
+        let mut in_range = values[5..15].to_vec();
+        in_range.sort();
+        assert_eq!(&in_range[..], &original[5..15]);
+
+        // Empty and full ranges.
+        ga_ctx.shuffle_range(&mut values, 7, 7);
+        ga_ctx.shuffle_range(&mut values, 0, 20);
+        values.sort();
+        assert_eq!(values, original);
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn shuffle_range_out_of_bounds()
+    {
+        ga_test_setup("ga_random::shuffle_range_out_of_bounds");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        ga_ctx.shuffle_range(&mut [1, 2, 3], 1, 4);
+    }
+
+
+    #[test]
+    fn state()
+    {
+        ga_test_setup("ga_random::state");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+
+        // Mixed draws, some of them take more than one u32.
+        for _ in 0..100
+        {
+            ga_ctx.gen::<f64>();
+            ga_ctx.gen_range(0, 17);
+            ga_ctx.gen::<f32>();
+        }
+
+        let state : GARandomState = ga_ctx.state();
+        assert!(state.seeded);
+        assert!(state.draws > 300);
+        assert_eq!(state.values_generated, 300);
+
+        let mut ga_ctx_2 = GARandomCtx::from_state(state, String::from("TestRandomCtx2"));
+        assert_eq!(ga_ctx_2.state(), state);
+        for _ in 0..100
+        {
+            assert_eq!(ga_ctx.gen::<f64>(), ga_ctx_2.gen::<f64>());
+            assert_eq!(ga_ctx.gen_range(0, 17), ga_ctx_2.gen_range(0, 17));
+        }
+
+        ga_ctx.reset();
+        assert_eq!(ga_ctx.state().draws, 0);
+
+        let unseeded = GARandomCtx::new_unseeded(String::from("TestRandomCtx3"));
+        let mut unseeded_2 = GARandomCtx::from_state(unseeded.state(), String::from("TestRandomCtx4"));
+        assert_eq!(GARandomCtx::new_unseeded(String::from("TestRandomCtx5")).gen::<u32>(), unseeded_2.gen::<u32>());
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn coin()
+    {
+        ga_test_setup("ga_random::coin");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+
+        let flips : Vec<bool> = (0..10000).map(|_| ga_ctx.coin()).collect();
+        assert_eq!(ga_ctx.values_generated, 10000);
+        let heads = flips.iter().filter(|f| **f).count() as f32 / 10000.0;
+        assert!((heads - 0.5).abs() < 0.02, "heads fraction {}", heads);
+
+        let flips_2 : Vec<bool> = (0..10000).map(|_| ga_ctx_2.coin()).collect();
+        assert_eq!(flips, flips_2);
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn gen_ratio()
+    {
+        ga_test_setup("ga_random::gen_ratio");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+
+        assert!((0..1000).all(|_| !ga_ctx.gen_ratio(0, 7)));
+        assert!((0..1000).all(|_| ga_ctx.gen_ratio(7, 7)));
+        assert!((0..1000).all(|_| ga_ctx.gen_ratio(u32::MAX, u32::MAX)));
+
+        let hits = (0..10000).filter(|_| ga_ctx.gen_ratio(1, 2)).count() as f32 / 10000.0;
+        assert!((hits - 0.5).abs() < 0.02, "hits fraction {}", hits);
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn gen_ratio_invalid()
+    {
+        ga_test_setup("ga_random::gen_ratio_invalid");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        ga_ctx.gen_ratio(1, 0);
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn gen_gaussian()
+    {
+        ga_test_setup("ga_random::gen_gaussian");
+        let seed : GASeed = [1,2,3,4];
+        let mut ga_ctx = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+
+        let n = 20000;
+        let samples : Vec<f64> = (0..n).map(|_| ga_ctx.gen_gaussian(3.0, 2.0)).collect();
+        assert_eq!(ga_ctx.values_generated, n as u64);
+
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1) as f64;
+        assert!((mean - 3.0).abs() < 0.05, "mean {}", mean);
+        assert!((variance - 4.0).abs() < 0.2, "variance {}", variance);
+
+        let samples_2 : Vec<f64> = (0..n).map(|_| ga_ctx_2.gen_gaussian(3.0, 2.0)).collect();
+        assert_eq!(samples, samples_2);
+
+        ga_ctx.reset();
+        ga_ctx_2.reset();
+        for _ in 0..100
+        {
+            assert_eq!(ga_ctx.gen_gaussian_f32(-1.0, 0.5), ga_ctx_2.gen_gaussian_f32(-1.0, 0.5));
+        }
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn choose_weighted()
+    {
+        ga_test_setup("ga_random::choose_weighted");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx2"));
+
+        let items = ['a', 'b', 'c', 'd'];
+        let weights = [1.0, 0.0, 3.0, 6.0];
+        let mut counts = [0; 4];
+        for _ in 0..10000
+        {
+            let chosen = *ga_ctx.choose_weighted(&items, &weights);
+            assert_eq!(chosen, *ga_ctx_2.choose_weighted(&items, &weights));
+            counts[items.iter().position(|i| *i == chosen).unwrap()] += 1;
+        }
+
+        assert_eq!(counts[1], 0);
+        for (count, w) in counts.iter().zip(weights.iter())
+        {
+            let expected = w / 10.0;
+            assert!((*count as f32 / 10000.0 - expected).abs() < 0.02, "{:?}", counts);
+        }
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn choose_weighted_zero_weights()
+    {
+        ga_test_setup("ga_random::choose_weighted_zero_weights");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        ga_ctx.choose_weighted(&[1, 2], &[0.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn choose_weighted_negative_weight()
+    {
+        ga_test_setup("ga_random::choose_weighted_negative_weight");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        ga_ctx.choose_weighted(&[1, 2], &[2.0, -1.0]);
+    }
+
+    #[test]
+    fn sample_indices()
+    {
+        ga_test_setup("ga_random::sample_indices");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx2"));
+
+        for k in 0..21
+        {
+            let mut indices = ga_ctx.sample_indices(20, k);
+            assert_eq!(indices, ga_ctx_2.sample_indices(20, k));
+            assert_eq!(indices.len(), k);
+            assert!(indices.iter().all(|i| *i < 20));
+
+            indices.sort();
+            indices.dedup();
+            assert_eq!(indices.len(), k);
+        }
+        assert!(ga_ctx.sample_indices(0, 0).is_empty());
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn sample_indices_too_many()
+    {
+        ga_test_setup("ga_random::sample_indices_too_many");
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        ga_ctx.sample_indices(3, 4);
+    }
+
+
+    #[test]
+    fn jump()
+    {
+        ga_test_setup("ga_random::jump");
+        let seed : GASeed = [1,2,3,4];
+        let mut base = GARandomCtx::from_seed(seed, String::from("TestRandomCtx"));
+        let mut jumped = GARandomCtx::from_seed(seed, String::from("TestRandomCtx2"));
+        let mut jumped_2 = GARandomCtx::from_seed(seed, String::from("TestRandomCtx3"));
+
+        jumped.jump();
+        jumped_2.jump();
+        assert_eq!(jumped.values_generated, 0);
+
+        let base_values : Vec<u32> = (0..1000).map(|_| base.gen::<u32>()).collect();
+        let jumped_values : Vec<u32> = (0..1000).map(|_| jumped.gen::<u32>()).collect();
+        assert!(jumped_values.iter().zip(base_values.iter()).all(|(j, b)| j != b));
+        assert!(jumped_values.iter().zip(base_values.iter().skip(1)).all(|(j, b)| j != b));
+
+        let jumped_values_2 : Vec<u32> = (0..1000).map(|_| jumped_2.gen::<u32>()).collect();
+        assert_eq!(jumped_values, jumped_values_2);
+
+        // Snapshots survive jumps.
+        jumped.jump();
+        let mut restored = GARandomCtx::from_state(jumped.state(), String::from("TestRandomCtx4"));
+        assert_eq!(restored.gen::<u64>(), jumped.gen::<u64>());
+
+        jumped.reset();
+        base.reset();
+        assert_eq!(jumped.gen::<u32>(), base.gen::<u32>());
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn jump_distance()
+    {
+        ga_test_setup("ga_random::jump_distance");
+        // x^1000 modulo the xorshift128 characteristic polynomial: jumps 4 +
+        // 1000 outputs.
+        let poly = [0x6f466622, 0x306c09cb, 0xfc255937, 0x0316f09d];
+        let mut ga_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx"));
+        let mut ga_ctx_2 = GARandomCtx::from_seed([1,2,3,4], String::from("TestRandomCtx2"));
+
+        ga_ctx.gen::<f64>();
+        ga_ctx_2.gen::<f64>();
+        ga_ctx.rng.jump(poly);
+        ga_ctx_2.discard(1004);
+        for _ in 0..100
+        {
+            assert_eq!(ga_ctx.rng.next_u32(), ga_ctx_2.rng.next_u32());
+        }
+        ga_test_teardown();
+    }
 }
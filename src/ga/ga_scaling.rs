@@ -6,8 +6,8 @@
 //!
 //! Scales the raw score of a population's individuals.
 
-use ::ga::ga_core::GAIndividual;
-use ::ga::ga_population::GAPopulation;
+use ::ga::ga_core::{GADiversity, GAIndividual};
+use ::ga::ga_population::{GAPopulation, GAPopulationSortOrder};
 
 /// Scaling Scheme Trait
 /// 
@@ -48,7 +48,7 @@ pub struct GALinearScaling
 const GA_LINEAR_SCALING_MULTIPLIER : f32 = 2.0;
 impl GALinearScaling
 {
-    fn new(mult: f32) -> GALinearScaling
+    pub fn new(mult: f32) -> GALinearScaling
     {
         GALinearScaling{ multiplier: mult }
     }
@@ -60,6 +60,11 @@ impl GALinearScaling
         let b;
         let delta;
 
+        if max == min
+        {
+            // All scores are equal, nothing to scale.
+            return (1.0, 0.0);
+        }
         if min > ((m*avg - max) / (m - 1.0))
         {
             delta = max - avg;
@@ -81,12 +86,14 @@ impl<T: GAIndividual> GAScaling<T> for GALinearScaling
 {
     fn evaluate(&self, pop : &mut GAPopulation<T>)
     {
+        let avg = match pop.statistics()
+        {
+            Some(stats) => stats.raw_avg,
+            None => { return; }
+        };
         let max = pop.best_by_raw_score().raw();
         let min = pop.worst_by_raw_score().raw();
 
-        // TODO: avg should be part of GAPopulation
-        let avg = (max - min) / 2.0;
-
         let (a, b) = self.prescale(max, min, avg);
 
         let pop_vec = pop.population();
@@ -98,6 +105,160 @@ impl<T: GAIndividual> GAScaling<T> for GALinearScaling
     }
 }
 
+/// Sigma Truncation Scaling
+/// Sets fitness to ```max(0, raw - (avg - c*std_dev))```, with the mean and
+/// standard deviation of the population's raw scores. Unlike linear scaling,
+/// works with negative raw scores, and fitness is never negative.
+/// Under `GAPopulationSortOrder::LowIsBest` the offset is mirrored, and
+/// negated so lower fitness is still better:
+/// ```-max(0, (avg + c*std_dev) - raw)```, never positive. Either way the
+/// worst individuals are truncated to 0.
+/// Goldberg suggests ```c``` between 1 and 3.
+pub struct GASigmaTruncationScaling
+{
+    c: f32
+}
+
+const GA_SIGMA_TRUNCATION_MULTIPLIER : f32 = 2.0;
+impl GASigmaTruncationScaling
+{
+    pub fn new(c: f32) -> GASigmaTruncationScaling
+    {
+        GASigmaTruncationScaling{ c: c }
+    }
+}
+
+impl Default for GASigmaTruncationScaling
+{
+    fn default() -> GASigmaTruncationScaling
+    {
+        GASigmaTruncationScaling::new(GA_SIGMA_TRUNCATION_MULTIPLIER)
+    }
+}
+
+impl<T: GAIndividual> GAScaling<T> for GASigmaTruncationScaling
+{
+    fn evaluate(&self, pop: &mut GAPopulation<T>)
+    {
+        let order = pop.order();
+        let offset = match pop.statistics()
+        {
+            Some(stats) => match order
+            {
+                GAPopulationSortOrder::HighIsBest => stats.raw_avg - self.c * stats.raw_std_dev,
+                GAPopulationSortOrder::LowIsBest => stats.raw_avg + self.c * stats.raw_std_dev
+            },
+            None => { return; }
+        };
+
+        let pop_vec = pop.population();
+        for ind in pop_vec
+        {
+            let rs = ind.raw();
+            match order
+            {
+                GAPopulationSortOrder::HighIsBest => ind.set_fitness((rs - offset).max(0.0)),
+                GAPopulationSortOrder::LowIsBest => ind.set_fitness(-(offset - rs).max(0.0))
+            }
+        }
+    }
+}
+
+/// Species Scaling
+/// NEAT-style explicit fitness sharing. The population is split into species
+/// with `GAPopulation::cluster(threshold)` and each individual's raw score is
+/// divided by the size of its species (multiplied under
+/// `GAPopulationSortOrder::LowIsBest`), so large species don't take over the
+/// population. Raw scores are expected to be non-negative.
+pub struct GASpeciesScaling
+{
+    threshold: f32
+}
+
+impl GASpeciesScaling
+{
+    /// `threshold` is the similarity above which 2 individuals belong to the
+    /// same species.
+    pub fn new(threshold: f32) -> GASpeciesScaling
+    {
+        GASpeciesScaling{ threshold: threshold }
+    }
+}
+
+impl<T: GADiversity> GAScaling<T> for GASpeciesScaling
+{
+    fn evaluate(&self, pop: &mut GAPopulation<T>)
+    {
+        let species = pop.cluster(self.threshold);
+        let order = pop.order();
+
+        let pop_vec = pop.population();
+        for s in species
+        {
+            let size = s.len() as f32;
+            for i in s
+            {
+                let rs = pop_vec[i].raw();
+                match order
+                {
+                    GAPopulationSortOrder::HighIsBest => pop_vec[i].set_fitness(rs / size),
+                    GAPopulationSortOrder::LowIsBest => pop_vec[i].set_fitness(rs * size)
+                }
+            }
+        }
+    }
+}
+
+
+/// Fitness Sharing
+/// Goldberg and Richardson's niching: each individual's raw score is divided
+/// by its niche count, the sum of the sharing function
+/// ```1 - (d/sigma_share)^alpha``` over the individuals within
+/// ```sigma_share``` of it, itself included (see `GAPopulation::niche_counts`).
+/// Distances are genotypic (`GADiversity::distance`). Crowded optima are
+/// penalized, so the population spreads over several of them.
+/// Dividing only penalizes crowding for non-negative scores that are
+/// maximized: `evaluate` panics on a LowIsBest population or a negative raw
+/// score.
+pub struct GASharing
+{
+    sigma_share: f32,
+    alpha: f32
+}
+
+const GA_SHARING_ALPHA : f32 = 1.0;
+impl GASharing
+{
+    /// Triangular sharing function (```alpha``` 1).
+    pub fn new(sigma_share: f32) -> GASharing
+    {
+        GASharing::new_with_alpha(sigma_share, GA_SHARING_ALPHA)
+    }
+
+    pub fn new_with_alpha(sigma_share: f32, alpha: f32) -> GASharing
+    {
+        GASharing{ sigma_share: sigma_share, alpha: alpha }
+    }
+}
+
+impl<T: GADiversity> GAScaling<T> for GASharing
+{
+    fn evaluate(&self, pop: &mut GAPopulation<T>)
+    {
+        assert!(pop.order() == GAPopulationSortOrder::HighIsBest, "GASharing - Raw scores must be maximized");
+        let counts = pop.niche_counts(self.sigma_share, self.alpha);
+
+        let pop_vec = pop.population();
+        for (ind, count) in pop_vec.iter_mut().zip(counts.iter())
+        {
+            // Individuals share with themselves too.
+            let rs = ind.raw();
+            assert!(rs >= 0.0, "GASharing - Raw scores must be non-negative");
+            ind.set_fitness(rs / (1.0 + count));
+        }
+    }
+}
+
 
 ////////////////////////////////////////
 // Tests
@@ -130,18 +291,197 @@ mod test
     #[test]
     fn linear_scaling()
     {
-        ga_test_setup("ga_scaling::no_scaling");
+        ga_test_setup("ga_scaling::linear_scaling");
         let f = GA_TEST_FITNESS_VAL;
         let mut population = GAPopulation::new(vec![GATestIndividual::new(f)], GAPopulationSortOrder::HighIsBest);
         population.sort();
 
-        let scaler = GALinearScaling{ multiplier: super::GA_LINEAR_SCALING_MULTIPLIER };
+        let scaler = GALinearScaling::new(super::GA_LINEAR_SCALING_MULTIPLIER);
+
+        scaler.evaluate(&mut population);
+
+        // A single score is left as is.
+        assert_eq!(population.individual(0, GAPopulationSortBasis::Raw).fitness(),
+                   population.individual(0, GAPopulationSortBasis::Raw).score());
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn linear_scaling_skewed()
+    {
+        ga_test_setup("ga_scaling::linear_scaling_skewed");
+        // Mean 2.0, midrange 3.5.
+        let raws = vec![1.0, 1.0, 6.0, 1.0, 1.0];
+        let inds = raws.iter().map(|rs| GATestIndividual::new(*rs)).collect();
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+        population.sort();
+
+        let scaler = GALinearScaling::new(super::GA_LINEAR_SCALING_MULTIPLIER);
+
+        scaler.evaluate(&mut population);
+
+        // The mean maps to itself and the best to 2x the mean:
+        // fitness = 0.5*raw + 1.0
+        let fitness : Vec<f32> = population.population().iter().map(|ind| ind.fitness()).collect();
+        assert_eq!(fitness, vec![1.5, 1.5, 4.0, 1.5, 1.5]);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn sigma_truncation_scaling()
+    {
+        ga_test_setup("ga_scaling::sigma_truncation_scaling");
+        let raws = vec![-10.0, -3.0, 0.5, -7.0, 4.0, -1.0, -40.0];
+        let inds = raws.iter().map(|rs| GATestIndividual::new(*rs)).collect();
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+
+        let scaler = GASigmaTruncationScaling::new(1.0);
+
+        scaler.evaluate(&mut population);
+
+        let stats = population.statistics().unwrap();
+        let offset = stats.raw_avg - stats.raw_std_dev;
+        for ind in population.population().iter()
+        {
+            assert!(ind.fitness() >= 0.0);
+            assert_eq!(ind.fitness(), (ind.raw() - offset).max(0.0));
+        }
+
+        // Ordering is preserved, the outlier is truncated to 0.
+        population.force_sort();
+        let by_raw : Vec<f32> = population.raw_score_iterator().map(|ind| ind.raw()).collect();
+        let by_fitness : Vec<f32> = population.fitness_score_iterator().map(|ind| ind.raw()).collect();
+        assert_eq!(by_raw, by_fitness);
+        assert_eq!(population.worst_by_fitness_score().fitness(), 0.0);
+
+        // Minimizing the opposite scores, the outlier is still the one
+        // truncated.
+        let inds = raws.iter().map(|rs| GATestIndividual::new(-*rs)).collect();
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::LowIsBest);
+
+        scaler.evaluate(&mut population);
+
+        let stats = population.statistics().unwrap();
+        let offset = stats.raw_avg + stats.raw_std_dev;
+        for ind in population.population().iter()
+        {
+            assert!(ind.fitness() <= 0.0);
+            assert_eq!(ind.fitness(), -(offset - ind.raw()).max(0.0));
+        }
+
+        population.force_sort();
+        let by_raw : Vec<f32> = population.raw_score_iterator().map(|ind| ind.raw()).collect();
+        let by_fitness : Vec<f32> = population.fitness_score_iterator().map(|ind| ind.raw()).collect();
+        assert_eq!(by_raw, by_fitness);
+        assert_eq!(population.worst_by_fitness_score().raw(), 40.0);
+        assert_eq!(population.worst_by_fitness_score().fitness(), 0.0);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn sharing()
+    {
+        ga_test_setup("ga_scaling::sharing");
+        // Equally good optima, one crowded (4 individuals) and one sparse (2).
+        let raws = vec![10.0, 30.0, 10.0, 10.0, 30.0, 10.0];
+        let inds = raws.iter().map(|rs| GATestIndividual::new(*rs)).collect();
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+
+        let scaler = GASharing::new(5.0);
 
         scaler.evaluate(&mut population);
 
-        // TODO: Real test
-        assert!(population.individual(0, GAPopulationSortBasis::Raw).fitness() !=
-                population.individual(0, GAPopulationSortBasis::Raw).raw());
+        let fitness : Vec<f32> = population.population().iter().map(|ind| ind.fitness()).collect();
+        assert_eq!(fitness, vec![10.0/4.0, 30.0/2.0, 10.0/4.0, 10.0/4.0, 30.0/2.0, 10.0/4.0]);
+
+        // Within the radius, closer neighbors count more.
+        let raws = vec![10.0, 11.0, 12.0, 30.0];
+        let inds = raws.iter().map(|rs| GATestIndividual::new(*rs)).collect();
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+
+        GASharing::new(2.0).evaluate(&mut population);
+
+        let fitness : Vec<f32> = population.population().iter().map(|ind| ind.fitness()).collect();
+        assert_eq!(fitness, vec![10.0/1.5, 11.0/2.0, 12.0/1.5, 30.0]);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn sharing_genotypic()
+    {
+        ga_test_setup("ga_scaling::sharing_genotypic");
+        use ::ga::ga_individuals::GABinaryIndividual;
+
+        // Same raw scores, but only the first 2 genomes are close.
+        let genomes = vec![vec![true; 8], vec![true; 8], vec![false; 8]];
+        let inds = genomes.into_iter().map(|bits|
+        {
+            let mut ind = GABinaryIndividual::new(bits);
+            ind.set_raw(12.0);
+            ind
+        }).collect();
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+
+        GASharing::new(4.0).evaluate(&mut population);
+
+        let fitness : Vec<f32> = population.population().iter().map(|ind| ind.fitness()).collect();
+        assert_eq!(fitness, vec![6.0, 6.0, 12.0]);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn sharing_low_is_best()
+    {
+        ga_test_setup("ga_scaling::sharing_low_is_best");
+        let inds = vec![GATestIndividual::new(10.0), GATestIndividual::new(30.0)];
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::LowIsBest);
+
+        GASharing::new(5.0).evaluate(&mut population);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sharing_negative_raw()
+    {
+        ga_test_setup("ga_scaling::sharing_negative_raw");
+        let inds = vec![GATestIndividual::new(10.0), GATestIndividual::new(-30.0)];
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+
+        GASharing::new(5.0).evaluate(&mut population);
+    }
+
+    #[test]
+    fn species_scaling()
+    {
+        ga_test_setup("ga_scaling::species_scaling");
+        // A species of 4 around 10.0 and one of 1 at 20.0.
+        let raws = vec![10.0, 10.1, 20.0, 10.2, 10.3];
+        let inds = raws.iter().map(|rs| GATestIndividual::new(*rs)).collect();
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+
+        let scaler = GASpeciesScaling::new(0.5);
+
+        scaler.evaluate(&mut population);
+
+        let fitness : Vec<f32> = population.population().iter().map(|ind| ind.fitness()).collect();
+        assert_eq!(fitness, vec![10.0/4.0, 10.1/4.0, 20.0, 10.2/4.0, 10.3/4.0]);
+
+        // Minimizing, the crowded species is penalized too.
+        let inds = raws.iter().map(|rs| GATestIndividual::new(*rs)).collect();
+        let mut population = GAPopulation::new(inds, GAPopulationSortOrder::LowIsBest);
+
+        scaler.evaluate(&mut population);
+
+        let fitness : Vec<f32> = population.population().iter().map(|ind| ind.fitness()).collect();
+        assert_eq!(fitness, vec![10.0*4.0, 10.1*4.0, 20.0, 10.2*4.0, 10.3*4.0]);
+        population.sort();
+        assert_eq!(population.best_by_fitness_score().raw(), 20.0);
 
         ga_test_teardown();
     }
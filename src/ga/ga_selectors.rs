@@ -23,6 +23,7 @@
 //! `GAUniformSelector`
 //! `GARouletteWheelSelector`
 //! `GATournamentSelector`
+//! `GATruncationSelector`
 //!
 //! # Examples
 use ::ga::ga_core::GAIndividual;
@@ -46,6 +47,15 @@ pub trait GASelector<T: GAIndividual>
     /// Each selector implements a different method of selection. Randomization 
     /// is a key aspect of all methods.
     fn select<'a, S: GAScoreSelection<T>>(&self, pop: &'a GAPopulation<T>, rng_ctx: &mut GARandomCtx) -> &'a T;
+
+    /// Select `n` individuals from the population.
+    ///
+    /// Default implementation calls `select` `n` times. Selectors that can
+    /// select many individuals more efficiently at once should override it.
+    fn select_n<'a, S: GAScoreSelection<T>>(&self, pop: &'a GAPopulation<T>, n: usize, rng_ctx: &mut GARandomCtx) -> Vec<&'a T>
+    {
+        (0..n).map(|_| self.select::<S>(pop, rng_ctx)).collect()
+    }
 }
 
 /// Selection score type basis.
@@ -151,7 +161,7 @@ impl<T: GAIndividual> GASelector<T> for GARankSelector
 {
     fn update<S: GAScoreSelection<T>>(&mut self, pop: &mut GAPopulation<T>)
     {
-        pop.sort();
+        pop.sort_basis(S::population_sort_basis());
     }
 
     fn select<'a, S: GAScoreSelection<T>>(&self, pop: &'a GAPopulation<T>, rng_ctx: &mut GARandomCtx) -> &'a T
@@ -170,13 +180,25 @@ impl<T: GAIndividual> GASelector<T> for GARankSelector
 /// Uniform selector.
 ///
 /// Select an individual at random, with equal probability.
-pub struct GAUniformSelector;
+pub struct GAUniformSelector
+{
+    // Draw from the sorted lists (sorting the population on update) or
+    // straight from the population vector.
+    sorted: bool
+}
 
 impl GAUniformSelector
 {
     pub fn new() -> GAUniformSelector
     {
-        GAUniformSelector
+        GAUniformSelector { sorted: true }
+    }
+
+    /// Uniform selector that doesn't need the population sorted: `update`
+    /// does nothing and `select` indexes the population vector directly.
+    pub fn new_unsorted() -> GAUniformSelector
+    {
+        GAUniformSelector { sorted: false }
     }
 }
 
@@ -186,12 +208,20 @@ impl<T: GAIndividual> GASelector<T> for GAUniformSelector
     {
         // Need to sort first, because GAPopulation.individual() draws individuals
         // from the sorted lists.
-        pop.sort();
+        if self.sorted
+        {
+            pop.sort();
+        }
     }
 
     // Select any individual at random.
     fn select<'a, S: GAScoreSelection<T>>(&self, pop: &'a GAPopulation<T>, rng_ctx: &mut GARandomCtx) -> &'a T
     {
+        if !self.sorted
+        {
+            return pop.random_individual(rng_ctx);
+        }
+
         // Since selection is at random, it doesn't matter where the individual
         // is drawn from, the raw-score-sorted or the fitness-score-sorted list.
         pop.individual(
@@ -200,11 +230,28 @@ impl<T: GAIndividual> GASelector<T> for GAUniformSelector
     }
 }
 
+/// Relative tolerance under which the max and min scores of a population are
+/// considered equal by `GARouletteWheelSelector`.
+const GA_ROULETTE_WHEEL_EPSILON : f32 = 1e-6;
+
+fn scores_are_equal(max_score: f32, min_score: f32) -> bool
+{
+    // The relative test can't tell anything apart from 0.
+    if max_score == min_score
+    {
+        return true;
+    }
+    (max_score - min_score).abs() <= GA_ROULETTE_WHEEL_EPSILON * max_score.abs().max(min_score.abs())
+}
+
 /// Roulette Wheel selector.
 ///
 /// Select an individual at random, each one having a probability of selection
 /// that is proportional to its score according to ranking (LowIsBest or 
 /// HighIsBest). 
+///
+/// When all scores are equal (within a small relative tolerance), the wheel
+/// falls back to uniform selection.
 pub struct GARouletteWheelSelector
 {
     wheel_proportions: Vec<f32>,
@@ -238,7 +285,10 @@ impl<T: GAIndividual> GASelector<T> for GARouletteWheelSelector
         let max_score = S::max_score(pop);
         let min_score = S::min_score(pop);
 
-        if max_score == min_score
+        // Scores that are (almost) all the same make for a uniform wheel: every
+        // slot gets the same share. Exact float equality is too fragile for this,
+        // near-equal scores would otherwise produce degenerate proportions.
+        if scores_are_equal(max_score, min_score)
         {
             // Upper bound is excluded.
             for i in 0 .. wheel_slots
@@ -343,59 +393,109 @@ impl<T: GAIndividual> GASelector<T> for GARouletteWheelSelector
 
 /// Tournament selector.
 ///
-/// Select 2 individuals using Roulette Wheel selection and select the best of the 2.
+/// Pick `k` individuals at random, with equal probability (the same individual
+/// may be picked more than once), and select the best of them according to
+/// population rank. `k` is 2 by default; larger tournaments favor the best
+/// individuals more, a tournament of 1 is uniform selection.
 pub struct GATournamentSelector
 {
-    roulette_wheel_selector: GARouletteWheelSelector,
+    uniform_selector: GAUniformSelector,
+    size: usize
 }
 
 impl GATournamentSelector
 {
-    pub fn new(p_size: usize) -> GATournamentSelector
+    /// Tournaments of 2 individuals. Uniform picks don't depend on the
+    /// population size, so `p_size` is ignored.
+    #[deprecated(note = "use GATournamentSelector::default or GATournamentSelector::with_size")]
+    pub fn new(_p_size: usize) -> GATournamentSelector
+    {
+        GATournamentSelector::default()
+    }
+
+    /// Tournaments of `k` individuals. Panics if `k` is 0.
+    pub fn with_size(k: usize) -> GATournamentSelector
     {
+        assert!(k > 0, "Tournaments need at least 1 individual");
         GATournamentSelector
         {
-            roulette_wheel_selector: GARouletteWheelSelector::new(p_size)
+            uniform_selector: GAUniformSelector::new(),
+            size: k
         }
     }
 }
 
+impl Default for GATournamentSelector
+{
+    /// Tournaments of 2 individuals.
+    fn default() -> GATournamentSelector
+    {
+        GATournamentSelector::with_size(2)
+    }
+}
+
 impl<T: GAIndividual> GASelector<T> for GATournamentSelector
 {
     fn update<S: GAScoreSelection<T>>(&mut self, pop: &mut GAPopulation<T>)
     {
-        self.roulette_wheel_selector.update::<S>(pop);
+        self.uniform_selector.update::<S>(pop);
     }
 
     fn select<'a, S: GAScoreSelection<T>>(&self, pop: &'a GAPopulation<T>, rng_ctx: &mut GARandomCtx) -> &'a T
     {
-        let low_score_individual;
-        let high_score_individual;
-        let individual1;
-        let individual2;
-
-        // Select 2 individuals using Roulette Wheel selection.
-        individual1 = self.roulette_wheel_selector.select::<S>(pop, rng_ctx);
-        individual2 = self.roulette_wheel_selector.select::<S>(pop, rng_ctx);
-
-        if S::score(individual1) 
-           >= S::score(individual2)
+        let mut winner = self.uniform_selector.select::<S>(pop, rng_ctx);
+        for _ in 1..self.size
         {
-            low_score_individual = individual2;
-            high_score_individual = individual1;
+            let contender = self.uniform_selector.select::<S>(pop, rng_ctx);
+
+            // Keep the individual that is best according to population rank.
+            let is_better = match pop.order()
+            {
+                GAPopulationSortOrder::HighIsBest => S::score(contender) > S::score(winner),
+                GAPopulationSortOrder::LowIsBest  => S::score(contender) < S::score(winner)
+            };
+            if is_better
+            {
+                winner = contender;
+            }
         }
-        else
+        winner
+    }
+}
+
+/// Truncation selector.
+///
+/// Select an individual at random, with equal probability, among the best
+/// `ceil(threshold * size)` of the population according to population rank.
+pub struct GATruncationSelector
+{
+    threshold: f32
+}
+
+impl GATruncationSelector
+{
+    /// Panics unless `threshold` is in (0, 1].
+    pub fn new(threshold: f32) -> GATruncationSelector
+    {
+        assert!(threshold > 0.0 && threshold <= 1.0, "Truncation threshold must be in (0, 1], got {}", threshold);
+        GATruncationSelector
         {
-            low_score_individual = individual1;
-            high_score_individual = individual2;
+            threshold: threshold
         }
+    }
+}
 
-        // Return the individual that is best according to population rank.
-        match pop.order()
-        {
-            GAPopulationSortOrder::HighIsBest => high_score_individual,
-            GAPopulationSortOrder::LowIsBest  => low_score_individual
-        } 
+impl<T: GAIndividual> GASelector<T> for GATruncationSelector
+{
+    fn update<S: GAScoreSelection<T>>(&mut self, pop: &mut GAPopulation<T>)
+    {
+        pop.sort();
+    }
+
+    fn select<'a, S: GAScoreSelection<T>>(&self, pop: &'a GAPopulation<T>, rng_ctx: &mut GARandomCtx) -> &'a T
+    {
+        let cutoff = cmp::min((self.threshold * pop.size() as f32).ceil() as usize, pop.size());
+        pop.individual(rng_ctx.gen_range(0, cmp::max(cutoff, 1)), S::population_sort_basis())
     }
 }
 
@@ -467,6 +567,29 @@ mod test
         ga_test_teardown();
     }
 
+    #[test]
+    fn test_uniform_selector_unsorted()
+    {
+        ga_test_setup("ga_selectors::test_uniform_selector_unsorted");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_uniform_selector_unsorted_rng"));
+
+        // Never sorted.
+        let mut population = GAPopulation::new((0..4).map(|i| GATestIndividual::new(i as f32 + 1.0)).collect(),
+                                               GAPopulationSortOrder::HighIsBest);
+
+        let mut uniform_selector = GAUniformSelector::new_unsorted();
+        uniform_selector.update::<GARawScoreSelection>(&mut population);
+
+        let mut seen = [0; 4];
+        for _ in 0..400
+        {
+            let selected_individual = uniform_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx);
+            seen[selected_individual.raw() as usize - 1] += 1;
+        }
+        assert!(seen.iter().all(|count| *count > 50), "{:?}", seen);
+        ga_test_teardown();
+    }
+
     #[test]
     #[allow(unused_variables)]
     fn test_roulette_wheel_selector()
@@ -508,41 +631,176 @@ mod test
     }
 
     #[test]
-    #[allow(unused_variables)]
     fn test_tournament_selector()
     {
-        // Just exercise the code.
-        // TODO: How to test when there is randomness?
-
         ga_test_setup("ga_selectors::test_tournament_selector");
-        let mut individuals = vec![];
-        let mut rng_ctx = GARandomCtx::new_unseeded(String::from("test_tournament_selector_rng"));
+        let seed = [1,2,3,4];
 
-        for i in 1 .. 20
+        let individuals = (1..11).map(|rs| GATestIndividual::new(rs as f32)).collect();
+        let mut population = GAPopulation::new(individuals, GAPopulationSortOrder::LowIsBest);
+
+        // Tournaments of 1 are uniform selection.
         {
-            individuals.push(GATestIndividual::new(rng_ctx.gen::<f32>()));
+            let mut tournament_selector = GATournamentSelector::with_size(1);
+            tournament_selector.update::<GARawScoreSelection>(&mut population);
+            let mut uniform_selector = GAUniformSelector::new();
+            uniform_selector.update::<GARawScoreSelection>(&mut population);
+
+            let mut rng_ctx = GARandomCtx::from_seed(seed, String::from("test_tournament_selector_rng"));
+            let mut rng_ctx_2 = GARandomCtx::from_seed(seed, String::from("test_tournament_selector_rng_2"));
+            for _ in 0..100
+            {
+                assert_eq!(tournament_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx).raw(),
+                           uniform_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx_2).raw());
+            }
         }
 
-        let mut population
-          = GAPopulation::new(individuals, GAPopulationSortOrder::LowIsBest);
+        // Big tournaments almost always select the best.
+        {
+            let mut tournament_selector = GATournamentSelector::with_size(50);
+            tournament_selector.update::<GARawScoreSelection>(&mut population);
+
+            let mut rng_ctx = GARandomCtx::from_seed(seed, String::from("test_tournament_selector_rng"));
+            let best = (0..100).filter(|_| tournament_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx).raw() == 1.0).count();
+            assert!(best > 90);
 
+            // Fitness (1/raw) is LowIsBest too.
+            let worst = tournament_selector.select::<GAFitnessScoreSelection>(&population, &mut rng_ctx);
+            assert!(worst.fitness() <= 1.0 / 9.0);
+        }
+
+        // Default tournaments of 2 lean toward the best.
         {
-            let mut raw_tournament_selector 
-              = GARouletteWheelSelector::new(population.size());
+            let mut tournament_selector = GATournamentSelector::default();
+            tournament_selector.update::<GARawScoreSelection>(&mut population);
+
+            let mut rng_ctx = GARandomCtx::from_seed(seed, String::from("test_tournament_selector_rng"));
+            let sum : f32 = (0..1000).map(|_| tournament_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx).raw()).sum();
+            assert!(sum / 1000.0 < 5.5);
 
-            raw_tournament_selector.update::<GARawScoreSelection>(&mut population);
+            // The old constructor still builds tournaments of 2.
+            #[allow(deprecated)]
+            let mut old_selector = GATournamentSelector::new(population.size());
+            old_selector.update::<GARawScoreSelection>(&mut population);
 
-            raw_tournament_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx);
+            let mut rng_ctx = GARandomCtx::from_seed(seed, String::from("test_tournament_selector_rng"));
+            let old_sum : f32 = (0..1000).map(|_| old_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx).raw()).sum();
+            assert_eq!(old_sum, sum);
         }
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tournament_selector_empty_tournament()
+    {
+        ga_test_setup("ga_selectors::test_tournament_selector_empty_tournament");
+        GATournamentSelector::with_size(0);
+    }
+
+    #[test]
+    fn test_roulette_wheel_selector_near_equal_scores()
+    {
+        ga_test_setup("ga_selectors::test_roulette_wheel_selector_near_equal_scores");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_roulette_wheel_selector_near_equal_scores_rng"));
 
+        for order in vec![GAPopulationSortOrder::HighIsBest, GAPopulationSortOrder::LowIsBest]
         {
-            let mut fitness_tournament_selector 
-              = GARouletteWheelSelector::new(population.size());
+            // Scores differ only in the 7th decimal.
+            let mut population
+              = GAPopulation::new(vec![GATestIndividual::new(1.0000001),
+                                       GATestIndividual::new(1.0000002),
+                                       GATestIndividual::new(1.0000003),
+                                       GATestIndividual::new(1.0)],
+                                  order);
 
-            fitness_tournament_selector.update::<GAFitnessScoreSelection>(&mut population);
+            let mut roulette_wheel_selector = GARouletteWheelSelector::new(population.size());
+            roulette_wheel_selector.update::<GARawScoreSelection>(&mut population);
 
-            fitness_tournament_selector.select::<GAFitnessScoreSelection>(&population, &mut rng_ctx);
+            // Uniform fallback.
+            assert_eq!(roulette_wheel_selector.wheel_proportions, vec![0.25, 0.5, 0.75, 1.0]);
+
+            for _ in 0..100
+            {
+                let selected = roulette_wheel_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx);
+                assert!(!selected.raw().is_nan());
+            }
+        }
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_roulette_wheel_selector_small_scores()
+    {
+        ga_test_setup("ga_selectors::test_roulette_wheel_selector_small_scores");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_roulette_wheel_selector_small_scores_rng"));
+
+        // Tiny, but 5 times apart: not a uniform wheel.
+        let individuals = (1..6).map(|i| GATestIndividual::new(i as f32 * 1e-7)).collect();
+        let mut population = GAPopulation::new(individuals, GAPopulationSortOrder::HighIsBest);
+
+        let mut roulette_wheel_selector = GARouletteWheelSelector::new(population.size());
+        roulette_wheel_selector.update::<GARawScoreSelection>(&mut population);
+        assert!(roulette_wheel_selector.wheel_proportions != vec![0.2, 0.4, 0.6, 0.8, 1.0]);
+        // The best individual gets 5/15 of the wheel.
+        assert!((roulette_wheel_selector.wheel_proportions[0] - 5.0 / 15.0).abs() < 1e-5);
+
+        let best = (0..1000).filter(|_| roulette_wheel_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx).raw() == 5e-7).count();
+        assert!(best > 250 && best < 420, "{}", best);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_truncation_selector()
+    {
+        ga_test_setup("ga_selectors::test_truncation_selector");
+        let mut rng_ctx = GARandomCtx::from_seed([1,2,3,4], String::from("test_truncation_selector_rng"));
+
+        let individuals = (1..101).map(|rs| GATestIndividual::new(rs as f32)).collect();
+        let mut population = GAPopulation::new(individuals, GAPopulationSortOrder::HighIsBest);
+
+        let mut truncation_selector = GATruncationSelector::new(0.1);
+        truncation_selector.update::<GARawScoreSelection>(&mut population);
+
+        let mut selected = vec![false; 10];
+        for _ in 0..1000
+        {
+            let raw = truncation_selector.select::<GARawScoreSelection>(&population, &mut rng_ctx).raw();
+            assert!(raw > 90.0);
+            selected[raw as usize - 91] = true;
         }
+        // Uniform among the top 10.
+        assert!(selected.iter().all(|s| *s));
+
+        // By fitness (1/raw), the best are the lowest raw scores.
+        truncation_selector.update::<GAFitnessScoreSelection>(&mut population);
+        for _ in 0..100
+        {
+            assert!(truncation_selector.select::<GAFitnessScoreSelection>(&population, &mut rng_ctx).raw() <= 10.0);
+        }
+
+        // Keeps at least 1 individual.
+        let mut small = GAPopulation::new(vec![GATestIndividual::new(1.0), GATestIndividual::new(2.0)],
+                                          GAPopulationSortOrder::HighIsBest);
+        truncation_selector.update::<GARawScoreSelection>(&mut small);
+        assert_eq!(truncation_selector.select::<GARawScoreSelection>(&small, &mut rng_ctx).raw(), 2.0);
         ga_test_teardown();
     }
+
+    #[test]
+    #[should_panic]
+    fn test_truncation_selector_zero_threshold()
+    {
+        ga_test_setup("ga_selectors::test_truncation_selector_zero_threshold");
+        GATruncationSelector::new(0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_truncation_selector_threshold_over_1()
+    {
+        ga_test_setup("ga_selectors::test_truncation_selector_threshold_over_1");
+        GATruncationSelector::new(1.5);
+    }
 }
@@ -1,12 +1,30 @@
 // Copyright 2016 Revolution Solid & Contributors.
 // author(s): sysnett
 // rust-monster is licensed under a MIT License.
-use ::ga::ga_core::{GAFactory, GAFlags, GeneticAlgorithm, GAIndividual};
-use ::ga::ga_population::{GAPopulation, GAPopulationSortBasis, GAPopulationSortOrder};
+use ::ga::ga_core::{GABatchEvaluate, GAFactory, GAFlags, GeneticAlgorithm, GAIndividual, LOG_FULL_STATS, LOG_GENERATION_SUMMARY};
+use ::ga::ga_population::{GALocalSearchMode, GAPopulation, GAPopulationSortOrder, GAReplacementPolicy};
 use ::ga::ga_random::{GARandomCtx, GASeed};
+#[cfg(feature = "serde")]
+use ::ga::ga_random::GARandomState;
+use ::ga::ga_scaling::{GANoScaling, GAScaling};
 use ::ga::ga_selectors::*;
+use ::ga::ga_statistics::GAStatistics;
 
 use std::any::Any;
+use std::cmp;
+use std::collections::VecDeque;
+#[cfg(feature = "serde")]
+use std::io::{Read, Write};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde_json;
+
+// Smallest old best score convergence is relative to.
+const GA_CONVERGENCE_EPSILON : f32 = 1e-6;
 
 /// Simple Evaluation Context
 /// Empty Evaluation Context 
@@ -15,6 +33,7 @@ struct SimpleEvaluationCtx;
 /// Simple Genetic Algorithm Config
 /// Genetic Algorithm Config Trait Implementation for the Simple Genetic Algorithm
 #[derive(Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SimpleGeneticAlgorithmCfg
 {
     pub d_seed : GASeed,
@@ -28,10 +47,45 @@ pub struct SimpleGeneticAlgorithmCfg
     pub population_sort_order : GAPopulationSortOrder,
 
     pub elitism : bool,
+    // Number of best individuals (by raw score) that survive into the next
+    // generation, in place of the last offspring (see
+    // `GAReplacementPolicy::Elitist`). `elitism: true` means at least 1.
+    pub elitism_count : usize,
+
+    // Convergence: done when the best raw score improved by less than
+    // `pconv` (relative to the old best) over the last `n_conv` generations.
+    // Off when `n_conv` is 0.
+    pub pconv : f32,
+    pub n_conv : usize,
+
+    // Done as soon as the best raw score reaches (or beats) this score.
+    pub target_score : Option<f32>,
+
+    // Local search (see `GAIndividual::local_optimize`) after each
+    // evaluation of the population. Off by default.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub local_search : GALocalSearchMode,
 
     pub flags                   : GAFlags, 
 }
 
+impl SimpleGeneticAlgorithmCfg
+{
+    /// Number of individuals preserved by elitism, taking both `elitism` and
+    /// `elitism_count` into account.
+    pub fn elite_count(&self) -> usize
+    {
+        if self.elitism
+        {
+            cmp::max(1, self.elitism_count)
+        }
+        else
+        {
+            self.elitism_count
+        }
+    }
+}
+
 /// Simple Genetic Algorithm 
 ///
 /// A basic implementation of a Genetic Algorithm.
@@ -46,6 +100,11 @@ pub struct SimpleGeneticAlgorithm<'a, T: GAIndividual>
   population : GAPopulation<T>,
   rng_ctx : GARandomCtx,
   eval_ctx: Option<&'a mut Any>,
+  batch_evaluator: Option<Box<GABatchEvaluate<T> + 'a>>,
+  scaling: Box<GAScaling<T> + 'a>,
+  statistics: GAStatistics<T>,
+  // Best raw scores of the last `n_conv`+1 generations.
+  recent_best: VecDeque<f32>,
 }
 impl<'a, T: GAIndividual> SimpleGeneticAlgorithm<'a, T>
 {
@@ -85,92 +144,285 @@ impl<'a, T: GAIndividual> SimpleGeneticAlgorithm<'a, T>
             }
         }
 
-        SimpleGeneticAlgorithm { current_generation: 0, config: cfg, population: p, rng_ctx: rng, eval_ctx: eval_ctx }
+        SimpleGeneticAlgorithm { current_generation: 0, config: cfg, population: p, rng_ctx: rng, eval_ctx: eval_ctx, batch_evaluator: None, scaling: Box::new(GANoScaling), statistics: GAStatistics::new(), recent_best: VecDeque::new() }
     }
-}
-impl<'a, T: GAIndividual + Clone> GeneticAlgorithm<T> for SimpleGeneticAlgorithm <'a, T>
-{
-    fn population(&mut self) -> &mut GAPopulation<T>
+
+    /// Evaluate the population with `evaluator`, as a whole, instead of
+    /// calling `evaluate` on each individual. Only the individuals that need
+    /// it are handed to `evaluator` (see `GAPopulation::evaluate_batch`).
+    pub fn set_batch_evaluator(&mut self, evaluator: Box<GABatchEvaluate<T> + 'a>)
     {
-        &mut self.population
+        self.batch_evaluator = Some(evaluator);
     }
 
-    fn initialize_internal(&mut self)
+    /// Derive fitness from raw scores with `scaling`, after each evaluation
+    /// of the population. `GANoScaling` (fitness is the raw score) by default.
+    /// Parents are selected on fitness, so this sets the selection pressure.
+    pub fn set_scaling(&mut self, scaling: Box<GAScaling<T> + 'a>)
     {
-        assert!(self.population().size() > 0);
+        self.scaling = scaling;
+    }
+
+    /// Evaluate individuals with `eval_ctx`, e.g. after `load_checkpoint`.
+    pub fn set_eval_ctx(&mut self, eval_ctx: Option<&'a mut Any>)
+    {
+        self.eval_ctx = eval_ctx;
+    }
+
+    // Returns the number of individuals evaluated.
+    fn evaluate_population(&mut self, force: bool) -> usize
+    {
+        if let Some(ref evaluator) = self.batch_evaluator
+        {
+            return self.population.evaluate_batch(&**evaluator, force);
+        }
+
         match self.eval_ctx
         {
             Some(ref mut eval_ctx) =>
             {
-                self.population.evaluate(*eval_ctx);
+                self.population.evaluate(*eval_ctx, force)
             },
             None =>
             {
                 let mut v = SimpleEvaluationCtx{};
-                self.population.evaluate(&mut v as &mut Any);
+                self.population.evaluate(&mut v as &mut Any, force)
             }
         }
+    }
+
+    // Scale the (evaluated) population and sort it with the new fitness.
+    fn scale_population(&mut self)
+    {
+        self.population.scale(&*self.scaling);
         self.population.sort();
     }
 
-    fn step_internal(&mut self) -> i32
+    fn record_best(&mut self)
     {
-        let mut new_individuals : Vec<T> = vec![];
+        if self.config.n_conv == 0
+        {
+            return;
+        }
 
-        let mut roulette_selector = GARouletteWheelSelector::new(self.population.size());
-        roulette_selector.update::<GARawScoreSelection>(&mut self.population);
+        self.recent_best.push_back(self.population.best_by_raw_score().raw());
+        if self.recent_best.len() > self.config.n_conv + 1
+        {
+            self.recent_best.pop_front();
+        }
+    }
 
+    // Has the best score stopped improving (see `pconv` and `n_conv`)?
+    fn converged(&self) -> bool
+    {
+        if self.config.n_conv == 0 || self.recent_best.len() <= self.config.n_conv
+        {
+            return false;
+        }
 
-        // Create new individuals 
-        for _ in 0..self.population.size()
+        let old_best = self.recent_best[0];
+        let new_best = self.recent_best[self.recent_best.len() - 1];
+        let improvement = match self.population.order()
         {
-            let ind = roulette_selector.select::<GARawScoreSelection>(&self.population, &mut self.rng_ctx);
-            let mut new_ind = ind.clone();
-            if self.rng_ctx.test_value(self.config.probability_crossover)
+            GAPopulationSortOrder::HighIsBest => new_best - old_best,
+            GAPopulationSortOrder::LowIsBest => old_best - new_best
+        };
+
+        improvement / old_best.abs().max(GA_CONVERGENCE_EPSILON) < self.config.pconv
+    }
+
+    // Has the best score reached `target_score`?
+    fn target_reached(&mut self) -> bool
+    {
+        let target = match self.config.target_score
+        {
+            Some(target) => target,
+            None => return false
+        };
+
+        match self.population.statistics()
+        {
+            Some(stats) =>
             {
-                let ind_2 = roulette_selector.select::<GARawScoreSelection>(&self.population, &mut self.rng_ctx);
-                new_ind = *ind.crossover(ind_2, &mut self.rng_ctx);
-            }
+                match self.population.order()
+                {
+                    GAPopulationSortOrder::HighIsBest => stats.raw_max >= target,
+                    GAPopulationSortOrder::LowIsBest => stats.raw_min <= target
+                }
+            },
+            None => false
+        }
+    }
+
+    // Per-generation logging, gated by `LOG_GENERATION_SUMMARY` and
+    // `LOG_FULL_STATS`.
+    fn log_generation(&mut self)
+    {
+        let flags = self.config.flags;
+        if !flags.intersects(LOG_GENERATION_SUMMARY | LOG_FULL_STATS)
+        {
+            return;
+        }
 
-            new_ind.mutate(self.config.probability_mutation, &mut self.rng_ctx);
+        let stats = match self.population.statistics()
+        {
+            Some(stats) => stats,
+            None => return
+        };
 
-            new_individuals.push(new_ind);
+        if flags.contains(LOG_GENERATION_SUMMARY)
+        {
+            info!("Generation {}: best raw {} avg raw {} best fitness {} avg fitness {}",
+                  self.current_generation,
+                  self.population.best_by_raw_score().raw(), stats.raw_avg,
+                  self.population.best_by_fitness_score().fitness(), stats.fitness_avg);
         }
 
-        let best_old_individual = self.population.best(0, GAPopulationSortBasis::Fitness).clone();
+        if flags.contains(LOG_FULL_STATS)
+        {
+            debug!("Generation {} statistics:", self.current_generation);
+            self.population.print_statistics();
+        }
+    }
+}
+// Everything a SimpleGeneticAlgorithm needs to resume a run. Saved with
+// borrowed population and statistics, loaded with owned ones.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SimpleGeneticAlgorithmCheckpoint<P, S>
+{
+    current_generation: i32,
+    config: SimpleGeneticAlgorithmCfg,
+    population: P,
+    rng_state: GARandomState,
+    statistics: S,
+    recent_best: VecDeque<f32>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a, T: GAIndividual + Serialize + DeserializeOwned> SimpleGeneticAlgorithm<'a, T>
+{
+    /// Write the state of the run (generation, config, population, random
+    /// number generator and statistics) to `w`, as JSON.
+    ///
+    /// The evaluation context, the batch evaluator and the scaling scheme
+    /// aren't saved.
+    ///
+    /// Panics if writing fails.
+    pub fn save_checkpoint<W: Write>(&self, w: W)
+    {
+        let checkpoint = SimpleGeneticAlgorithmCheckpoint
+        {
+            current_generation: self.current_generation,
+            config: self.config,
+            population: &self.population,
+            rng_state: self.rng_ctx.state(),
+            statistics: &self.statistics,
+            recent_best: self.recent_best.clone(),
+        };
+        serde_json::to_writer(w, &checkpoint).expect("Simple Genetic Algorithm - failed to write checkpoint");
+    }
 
-        // Evaluate the new population
-        // TODO: Archive the old population
-        let order = self.population.order();
-        self.population = GAPopulation::new(new_individuals, order);
+    /// Resume a run saved with `save_checkpoint`. Stepping it gives the same
+    /// generations the saved GA would have.
+    ///
+    /// The GA has no evaluation context and no scaling, see `set_eval_ctx`,
+    /// `set_batch_evaluator` and `set_scaling`.
+    ///
+    /// Panics if the checkpoint can't be read.
+    pub fn load_checkpoint<R: Read>(r: R) -> SimpleGeneticAlgorithm<'a, T>
+    {
+        let checkpoint : SimpleGeneticAlgorithmCheckpoint<GAPopulation<T>, GAStatistics<T>> =
+            serde_json::from_reader(r).expect("Simple Genetic Algorithm - failed to read checkpoint");
+
+        SimpleGeneticAlgorithm
+        {
+            current_generation: checkpoint.current_generation,
+            config: checkpoint.config,
+            population: checkpoint.population,
+            rng_ctx: GARandomCtx::from_state(checkpoint.rng_state, String::from("")),
+            eval_ctx: None,
+            batch_evaluator: None,
+            scaling: Box::new(GANoScaling),
+            statistics: checkpoint.statistics,
+            recent_best: checkpoint.recent_best
+        }
+    }
+}
 
+impl<'a, T: GAIndividual + Clone> SimpleGeneticAlgorithm<'a, T>
+{
+    // Local search on the (evaluated) population, with the evaluation
+    // context.
+    fn local_optimize_population(&mut self)
+    {
+        let mode = self.config.local_search;
         match self.eval_ctx
         {
             Some(ref mut eval_ctx) =>
             {
-                self.population.evaluate(*eval_ctx);
+                self.population.local_optimize(mode, *eval_ctx);
             },
             None =>
             {
                 let mut v = SimpleEvaluationCtx{};
-                self.population.evaluate(&mut v as &mut Any);
+                self.population.local_optimize(mode, &mut v as &mut Any);
             }
         }
-        self.population.sort();
+    }
+}
 
-        if self.config.elitism
-        {
-            self.population.swap_individual(best_old_individual);
-            self.population.sort(); // I don't love the double sorting :(
-        }
+impl<'a, T: GAIndividual + Clone + PartialEq> GeneticAlgorithm<T> for SimpleGeneticAlgorithm <'a, T>
+{
+    fn population(&mut self) -> &mut GAPopulation<T>
+    {
+        &mut self.population
+    }
+
+    fn statistics(&self) -> &GAStatistics<T>
+    {
+        &self.statistics
+    }
+
+    fn initialize_internal(&mut self)
+    {
+        assert!(self.population().size() > 0);
+        self.evaluate_population(true);
+        self.local_optimize_population();
+        self.scale_population();
+        self.statistics.set_best(self.population.clone());
+        self.record_best();
+    }
+
+    fn step_internal(&mut self) -> i32
+    {
+        // Parents are selected on (scaled) fitness.
+        let size = self.population.size();
+        let new_individuals = self.population.breed::<GAFitnessScoreSelection>(size,
+                                                                              self.config.probability_crossover,
+                                                                              self.config.probability_mutation,
+                                                                              &mut self.rng_ctx);
+
+        // The elites (none without elitism) survive, and aren't re-evaluated.
+        // TODO: Archive the old population
+        self.population.replace_with_offspring(new_individuals, GAReplacementPolicy::Elitist(self.config.elite_count()));
+
+        // Evaluate the new population
+        let evaluations = self.evaluate_population(false);
+        self.local_optimize_population();
+        self.scale_population();
+
+        self.statistics.update_with_evaluations(&mut self.population, evaluations);
+        self.record_best();
 
         self.current_generation += 1;
+        self.log_generation();
         self.current_generation
     }
 
     fn done_internal(&mut self) -> bool
     {
-        self.current_generation >= self.config.max_generations 
+        self.current_generation >= self.config.max_generations || self.converged() || self.target_reached()
     }
 }
 
@@ -182,8 +434,11 @@ mod tests
     use ::ga::ga_test::*;
     use ::ga::ga_population::*;
     use ::ga::ga_core::*;
+    use ::ga::ga_scaling::GALinearScaling;
     use super::*;
 
+    use std::sync::mpsc::channel;
+
     fn simple_ga_validation(sga:&mut SimpleGeneticAlgorithm<GATestIndividual>)
     {
         sga.initialize();
@@ -271,4 +526,596 @@ mod tests
         //Not reached 
         ga_test_teardown();
     }
+
+    #[test]
+    fn elitism_count_test()
+    {
+        ga_test_setup("ga_simple::elitism_count_test");
+        let inds = (1..21).map(|rs| GATestIndividual::new(rs as f32)).collect();
+        let initial_population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+        let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                     SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                   d_seed : [1; 4],
+                                                   flags : DEBUG_FLAG,
+                                                   max_generations: 10,
+                                                   probability_crossover: 0.5,
+                                                   elitism_count: 3,
+                                                   ..Default::default()
+                                                 },
+                                                 None,
+                                                 Some(initial_population)
+                                                 );
+        ga.initialize();
+
+        while !ga.done()
+        {
+            let top_3 : Vec<f32> = ga.population().raw_score_iterator().take(3).map(|ind| ind.raw()).collect();
+            ga.step();
+            let new_raws : Vec<f32> = ga.population().raw_score_iterator().map(|ind| ind.raw()).collect();
+            assert_eq!(new_raws.len(), 20);
+            // The best never gets worse.
+            assert!(new_raws[0] >= top_3[0]);
+            for raw in top_3
+            {
+                assert!(new_raws.contains(&raw));
+            }
+        }
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn elite_count_cfg_test()
+    {
+        let mut cfg = SimpleGeneticAlgorithmCfg { ..Default::default() };
+        assert_eq!(cfg.elite_count(), 0);
+        cfg.elitism = true;
+        assert_eq!(cfg.elite_count(), 1);
+        cfg.elitism_count = 3;
+        assert_eq!(cfg.elite_count(), 3);
+        cfg.elitism = false;
+        assert_eq!(cfg.elite_count(), 3);
+    }
+
+    #[test]
+    fn batch_evaluator_test()
+    {
+        ga_test_setup("ga_simple::batch_evaluator_test");
+
+        struct GATestBatchEvaluator;
+        impl GABatchEvaluate<GATestIndividual> for GATestBatchEvaluator
+        {
+            fn evaluate_batch(&self, inds: &mut [GATestIndividual])
+            {
+                for (i, ind) in inds.iter_mut().enumerate()
+                {
+                    ind.set_raw(100.0 + i as f32);
+                }
+            }
+        }
+
+        let inds = (1..11).map(|rs| GATestIndividual::new(rs as f32)).collect();
+        let initial_population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+        let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                     SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                   d_seed : [1; 4],
+                                                   flags : DEBUG_FLAG,
+                                                   max_generations: 10,
+                                                   ..Default::default()
+                                                 },
+                                                 None,
+                                                 Some(initial_population)
+                                                 );
+        ga.set_batch_evaluator(Box::new(GATestBatchEvaluator));
+        ga.initialize();
+
+        // GATestIndividual::evaluate is a no-op, the scores come from the batch evaluator.
+        let raws : Vec<f32> = ga.population().raw_score_iterator().map(|ind| ind.raw()).collect();
+        let expected : Vec<f32> = (0..10).rev().map(|i| 100.0 + i as f32).collect();
+        assert_eq!(raws, expected);
+
+        ga.step();
+        assert_eq!(ga.population().best_by_raw_score().raw(), 109.0);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn self_adaptive_rates_test()
+    {
+        ga_test_setup("ga_simple::self_adaptive_rates_test");
+
+        #[derive(Clone, PartialEq)]
+        struct GARateIndividual
+        {
+            raw: f32,
+            fitness: f32,
+            // Probability the last mutate() was called with.
+            mutated_with: f32
+        }
+        impl GAIndividual for GARateIndividual
+        {
+            fn crossover(&self, _: &GARateIndividual, _: &mut Any) -> Box<GARateIndividual> { Box::new(self.clone()) }
+            fn mutate(&mut self, probability: f32, _: &mut Any) { self.mutated_with = probability; }
+            fn evaluate(&mut self, _: &mut Any) {}
+            fn fitness(&self) -> f32 { self.fitness }
+            fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+            fn raw(&self) -> f32 { self.raw }
+            fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+            fn mutate_rate(&self) -> Option<f32> { Some(0.75) }
+        }
+
+        let inds = (1..11).map(|rs| GARateIndividual { raw: rs as f32, fitness: rs as f32, mutated_with: -1.0 }).collect();
+        let initial_population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+        let mut ga : SimpleGeneticAlgorithm<GARateIndividual> =
+                     SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                   d_seed : [1; 4],
+                                                   flags : DEBUG_FLAG,
+                                                   max_generations: 10,
+                                                   probability_mutation: 0.1,
+                                                   ..Default::default()
+                                                 },
+                                                 None,
+                                                 Some(initial_population)
+                                                 );
+        ga.initialize();
+        ga.step();
+
+        assert!(ga.population().population().iter().all(|ind| ind.mutated_with == 0.75));
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn local_search_test()
+    {
+        ga_test_setup("ga_simple::local_search_test");
+
+        #[derive(Clone, PartialEq)]
+        struct GAClimbingIndividual
+        {
+            gene: f32,
+            raw: f32,
+            fitness: f32
+        }
+        impl GAIndividual for GAClimbingIndividual
+        {
+            fn crossover(&self, _: &GAClimbingIndividual, _: &mut Any) -> Box<GAClimbingIndividual> { Box::new(self.clone()) }
+            fn mutate(&mut self, _: f32, _: &mut Any) {}
+            fn evaluate(&mut self, _: &mut Any) { self.raw = self.gene; }
+            fn fitness(&self) -> f32 { self.fitness }
+            fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+            fn raw(&self) -> f32 { self.raw }
+            fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+            // One step uphill.
+            fn local_optimize(&mut self, _: &mut Any) -> f32 { self.gene += 1.0; self.gene }
+        }
+
+        for mode in vec![GALocalSearchMode::Lamarckian, GALocalSearchMode::Baldwinian]
+        {
+            let inds = (1..11).map(|g| GAClimbingIndividual { gene: g as f32, raw: 0.0, fitness: 0.0 }).collect();
+            let initial_population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+            let mut ga : SimpleGeneticAlgorithm<GAClimbingIndividual> =
+                         SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                       d_seed : [1; 4],
+                                                       flags : DEBUG_FLAG,
+                                                       max_generations: 10,
+                                                       local_search: mode,
+                                                       ..Default::default()
+                                                     },
+                                                     None,
+                                                     Some(initial_population)
+                                                     );
+            ga.initialize();
+
+            let inds = ga.population().population();
+            let genes : Vec<f32> = inds.iter().map(|ind| ind.gene).collect();
+            let expected_genes : Vec<f32> = match mode
+            {
+                GALocalSearchMode::Lamarckian => (2..12).map(|g| g as f32).collect(),
+                _ => (1..11).map(|g| g as f32).collect()
+            };
+            assert_eq!(genes, expected_genes);
+            // Selected (and scaled) on the improved score either way.
+            for (g, ind) in (1..11).zip(inds.iter())
+            {
+                assert_eq!(ind.raw, g as f32 + 1.0);
+                assert_eq!(ind.fitness, g as f32 + 1.0);
+            }
+        }
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn new_generation_test()
+    {
+        ga_test_setup("ga_simple::new_generation_test");
+        let mut factory = GATestFactory::new(GA_TEST_FITNESS_VAL);
+        let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                     SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                   d_seed : [1; 4],
+                                                   max_generations: 5,
+                                                   population_size: 10,
+                                                   ..Default::default()
+                                                 },
+                                                 Some(&mut factory as &mut GAFactory<GATestIndividual>),
+                                                 None
+                                                 );
+        ga.initialize();
+
+        let raw_scores = |ga: &mut SimpleGeneticAlgorithm<GATestIndividual>|
+        {
+            let mut scores : Vec<f32> = ga.population().population().iter().map(|ind| ind.raw()).collect();
+            scores.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            scores
+        };
+        let initial = raw_scores(&mut ga);
+
+        while !ga.done()
+        {
+            ga.step();
+        }
+
+        // Offspring replace the parents: roulette selection duplicates some
+        // individuals and drops others.
+        assert_eq!(ga.population().size(), 10);
+        assert!(raw_scores(&mut ga) != initial);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn run_with_progress_test()
+    {
+        ga_test_setup("ga_simple::run_with_progress_test");
+        let new_ga = |factory: &mut GATestFactory| -> SimpleGeneticAlgorithm<GATestIndividual>
+        {
+            SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                          d_seed : [1; 4],
+                                          max_generations: 5,
+                                          population_size: 10,
+                                          ..Default::default()
+                                        },
+                                        Some(factory as &mut GAFactory<GATestIndividual>),
+                                        None
+                                        )
+        };
+
+        let mut factory = GATestFactory::new(GA_TEST_FITNESS_VAL);
+        let mut ga = new_ga(&mut factory);
+        ga.initialize();
+
+        let (tx, rx) = channel();
+        ga.run_with_progress(tx);
+        let progress : Vec<(i32, f32)> = rx.iter().collect();
+
+        assert_eq!(progress.iter().map(|p| p.0).collect::<Vec<i32>>(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(progress[4].1, ga.population().best_by_raw_score().raw());
+        // The initial population is the first recorded generation.
+        assert_eq!(progress.iter().map(|p| p.1).collect::<Vec<f32>>(), ga.statistics().best_raw_series()[1..].to_vec());
+        assert!(ga.done());
+
+        // Dropping the receiver stops the run.
+        let mut factory = GATestFactory::new(GA_TEST_FITNESS_VAL);
+        let mut ga = new_ga(&mut factory);
+        ga.initialize();
+
+        let (tx, rx) = channel();
+        drop(rx);
+        ga.run_with_progress(tx);
+        assert!(!ga.done());
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn failed_crossover_test()
+    {
+        ga_test_setup("ga_simple::failed_crossover_test");
+
+        #[derive(Clone, PartialEq)]
+        struct GAPickyIndividual
+        {
+            raw: f32,
+            fitness: f32
+        }
+        impl GAIndividual for GAPickyIndividual
+        {
+            fn crossover(&self, _: &GAPickyIndividual, _: &mut Any) -> Box<GAPickyIndividual> { panic!("Use try_crossover") }
+            // Identical parents can't be recombined.
+            fn try_crossover(&self, other: &GAPickyIndividual, _: &mut Any) -> Option<Box<GAPickyIndividual>>
+            {
+                if self.raw == other.raw
+                {
+                    None
+                }
+                else
+                {
+                    Some(Box::new(GAPickyIndividual { raw: -1.0, fitness: -1.0 }))
+                }
+            }
+            fn mutate(&mut self, _: f32, _: &mut Any) {}
+            fn evaluate(&mut self, _: &mut Any) {}
+            fn fitness(&self) -> f32 { self.fitness }
+            fn set_fitness(&mut self, fitness: f32) { self.fitness = fitness; }
+            fn raw(&self) -> f32 { self.raw }
+            fn set_raw(&mut self, raw: f32) { self.raw = raw; }
+        }
+
+        let inds = vec![GAPickyIndividual { raw: 5.0, fitness: 5.0 }; 10];
+        let initial_population = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+        let mut ga : SimpleGeneticAlgorithm<GAPickyIndividual> =
+                     SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                   d_seed : [1; 4],
+                                                   max_generations: 3,
+                                                   probability_crossover: 1.0,
+                                                   ..Default::default()
+                                                 },
+                                                 None,
+                                                 Some(initial_population)
+                                                 );
+        ga.initialize();
+        while !ga.done()
+        {
+            ga.step();
+        }
+
+        // Every crossover failed: the parents were cloned.
+        assert_eq!(ga.population().size(), 10);
+        assert!(ga.population().population().iter().all(|ind| ind.raw == 5.0));
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn statistics_test()
+    {
+        ga_test_setup("ga_simple::statistics_test");
+        let mut factory = GATestFactory::new(GA_TEST_FITNESS_VAL);
+        let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                     SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                   d_seed : [1; 4],
+                                                   max_generations: 5,
+                                                   population_size: 10,
+                                                   ..Default::default()
+                                                 },
+                                                 Some(&mut factory as &mut GAFactory<GATestIndividual>),
+                                                 None
+                                                 );
+        ga.initialize();
+        assert!(ga.statistics().generation_statistics(1).is_some());
+
+        let mut best_raw = vec![ga.population().best_by_raw_score().raw()];
+        while !ga.done()
+        {
+            ga.step();
+            best_raw.push(ga.population().best_by_raw_score().raw());
+        }
+
+        // Generation #1 is the initial population.
+        assert_eq!(ga.statistics().cur_generation, 6);
+        for (i, best) in best_raw.iter().enumerate()
+        {
+            let stats = ga.statistics().generation_statistics(i + 1).unwrap();
+            assert_eq!(stats.raw_max, *best);
+        }
+        assert!(ga.statistics().generation_statistics(7).is_none());
+
+        let alltime_best = best_raw.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        assert_eq!(ga.statistics().best().unwrap().best_by_raw_score().raw(), alltime_best);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn convergence_test()
+    {
+        ga_test_setup("ga_simple::convergence_test");
+        let new_ga = |n_conv: usize| -> SimpleGeneticAlgorithm<GATestIndividual>
+        {
+            // GATestIndividuals don't change: the best score plateaus.
+            let initial_population = GAPopulation::new((1..11).map(|rs| GATestIndividual::new(rs as f32)).collect(),
+                                                       GAPopulationSortOrder::HighIsBest);
+            SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                          d_seed : [1; 4],
+                                          max_generations: 100,
+                                          elitism: true,
+                                          pconv: 0.01,
+                                          n_conv: n_conv,
+                                          ..Default::default()
+                                        },
+                                        None,
+                                        Some(initial_population)
+                                        )
+        };
+
+        let mut ga = new_ga(5);
+        ga.initialize();
+        let mut generations = 0;
+        while !ga.done()
+        {
+            generations = ga.step();
+        }
+        assert_eq!(generations, 5);
+        assert_eq!(ga.population().best_by_raw_score().raw(), 10.0);
+
+        // Off when n_conv is 0 (the default).
+        let mut ga = new_ga(0);
+        ga.initialize();
+        while !ga.done()
+        {
+            generations = ga.step();
+        }
+        assert_eq!(generations, 100);
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn target_score_test()
+    {
+        ga_test_setup("ga_simple::target_score_test");
+        for &(order, target, expected_generations) in [(GAPopulationSortOrder::HighIsBest, 10.0, 0),
+                                                      (GAPopulationSortOrder::HighIsBest, 10.5, 20),
+                                                      (GAPopulationSortOrder::LowIsBest, 1.0, 0),
+                                                      (GAPopulationSortOrder::LowIsBest, 0.5, 20)].iter()
+        {
+            let initial_population = GAPopulation::new((1..11).map(|rs| GATestIndividual::new(rs as f32)).collect(), order);
+            let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                         SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                       d_seed : [1; 4],
+                                                       max_generations: 20,
+                                                       target_score: Some(target),
+                                                       ..Default::default()
+                                                     },
+                                                     None,
+                                                     Some(initial_population)
+                                                     );
+            ga.initialize();
+            let mut generations = 0;
+            while !ga.done()
+            {
+                generations = ga.step();
+            }
+            assert_eq!(generations, expected_generations);
+        }
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn scaling_test()
+    {
+        ga_test_setup("ga_simple::scaling_test");
+        // Raw scores in fitness order after the first generation, without and
+        // with linear scaling.
+        let mut fitness_orders : Vec<Vec<f32>> = vec![];
+        for &linear in [false, true].iter()
+        {
+            let initial_population = GAPopulation::new((1..11).map(|rs| GATestIndividual::new(rs as f32)).collect(), GAPopulationSortOrder::HighIsBest);
+            let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                         SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                       d_seed : [1; 4],
+                                                       max_generations: 5,
+                                                       ..Default::default()
+                                                     },
+                                                     None,
+                                                     Some(initial_population)
+                                                     );
+            if linear
+            {
+                ga.set_scaling(Box::new(GALinearScaling::new(2.0)));
+            }
+            ga.initialize();
+            loop
+            {
+                let stats = ga.population().statistics().unwrap();
+                let fitness_is_raw = ga.population().population().iter().all(|ind| ind.fitness() == ind.raw());
+                if linear && stats.raw_max > stats.raw_min
+                {
+                    // Linear scaling keeps the mean and sends the worst to 0.
+                    assert!(!fitness_is_raw);
+                    assert!((stats.fitness_avg - stats.raw_avg).abs() < 1e-4, "{} {}", stats.fitness_avg, stats.raw_avg);
+                    assert!(stats.fitness_min.abs() < 1e-4, "{}", stats.fitness_min);
+                }
+                else
+                {
+                    // Without scaling (or anything to scale), GATestIndividual's
+                    // 1/raw fitness is replaced by the raw score.
+                    assert!(fitness_is_raw);
+                }
+                if ga.done()
+                {
+                    break;
+                }
+                ga.step();
+                if ga.current_generation == 1
+                {
+                    fitness_orders.push(ga.population().fitness_score_iterator().map(|ind| ind.raw()).collect());
+                }
+            }
+        }
+        // Same raw scores and seed, but parents are selected on the scaled
+        // fitness: the fitness-sorted populations differ.
+        assert!(fitness_orders[0] != fitness_orders[1], "{:?}", fitness_orders);
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn best_test()
+    {
+        ga_test_setup("ga_simple::best_test");
+        for &order in [GAPopulationSortOrder::HighIsBest, GAPopulationSortOrder::LowIsBest].iter()
+        {
+            let initial_population = GAPopulation::new((1..11).map(|rs| GATestIndividual::new(rs as f32)).collect(), order);
+            let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                         SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                                       d_seed : [1; 4],
+                                                       max_generations: 5,
+                                                       ..Default::default()
+                                                     },
+                                                     None,
+                                                     Some(initial_population)
+                                                     );
+            ga.initialize();
+            while !ga.done()
+            {
+                ga.step();
+            }
+            let best = ga.best().clone();
+            assert!(best == *ga.population().best_by_raw_score());
+            let stats = ga.population().statistics().unwrap();
+            let expected = if order == GAPopulationSortOrder::HighIsBest { stats.raw_max } else { stats.raw_min };
+            assert_eq!(best.raw(), expected);
+        }
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn checkpoint_test()
+    {
+        ga_test_setup("ga_simple::checkpoint_test");
+        let cfg = SimpleGeneticAlgorithmCfg {
+                    d_seed : [1; 4],
+                    max_generations: 10,
+                    population_size: 20,
+                    probability_crossover: 0.5,
+                    ..Default::default()
+                  };
+
+        let mut factory = GATestFactory::new(GA_TEST_FITNESS_VAL);
+        let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                     SimpleGeneticAlgorithm::new(cfg, Some(&mut factory as &mut GAFactory<GATestIndividual>), None);
+        ga.initialize();
+        while !ga.done()
+        {
+            ga.step();
+        }
+
+        let mut factory = GATestFactory::new(GA_TEST_FITNESS_VAL);
+        let mut ga_2 : SimpleGeneticAlgorithm<GATestIndividual> =
+                       SimpleGeneticAlgorithm::new(cfg, Some(&mut factory as &mut GAFactory<GATestIndividual>), None);
+        ga_2.initialize();
+        for _ in 0..2
+        {
+            ga_2.step();
+        }
+
+        let mut checkpoint = vec![];
+        ga_2.save_checkpoint(&mut checkpoint);
+        let mut resumed : SimpleGeneticAlgorithm<GATestIndividual> = SimpleGeneticAlgorithm::load_checkpoint(&checkpoint[..]);
+        assert!(resumed.rng_ctx.state() == ga_2.rng_ctx.state());
+
+        // Same generations as the saved GA...
+        while !resumed.done()
+        {
+            assert_eq!(resumed.step(), ga_2.step());
+            let raws : Vec<f32> = ga_2.population().population().iter().map(|ind| ind.raw()).collect();
+            let resumed_raws : Vec<f32> = resumed.population().population().iter().map(|ind| ind.raw()).collect();
+            assert_eq!(resumed_raws, raws);
+        }
+        assert!(ga_2.done());
+
+        // ... and as an uninterrupted run.
+        assert_eq!(resumed.population().best_by_raw_score().raw(), ga.population().best_by_raw_score().raw());
+        assert_eq!(resumed.statistics().cur_generation, ga.statistics().cur_generation);
+        ga_test_teardown();
+    }
 }
@@ -3,10 +3,18 @@
 // rust-monster is licensed under an MIT License.
 
 use std::cmp::Ordering::*;
+use std::f32;
+use std::time::{Duration, Instant};
 
 use ::ga::ga_core::GAIndividual;
 use ::ga::ga_population::{GAPopulation, GAPopulationStats, GAPopulationSortOrder};
 
+/// Genetic Algorithm Statistics
+///
+/// With the `serde` feature, statistics can be serialized (for individuals
+/// that can) to checkpoint long runs. The wall-clock timer isn't saved, the
+/// recorded `generation_times` are.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GAStatistics<T: GAIndividual>
 {
     // All statistics collected after last reset.
@@ -20,6 +28,15 @@ pub struct GAStatistics<T: GAIndividual>
     pub cur_generation: u32,            // aka curgen
     record_frequency: u32,              // aka scoreFreq
     record_diversity: bool,             // aka dodiv
+    record_timing: bool,
+
+    // Wall-clock time, only recorded when `record_timing` is set.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_time: Option<Instant>,
+    generation_times: Vec<Duration>,
+    // Individual evaluations of the generations in `generation_times`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    timed_evaluations: usize,
 
     pub alltime_best_pop: Option<GAPopulation<T>>,      // aka boa
     pub alltime_max_score: f32,                         // aka maxever
@@ -27,6 +44,9 @@ pub struct GAStatistics<T: GAIndividual>
     pub on_performance: f32,                            // aka on
     pub off_max_performance: f32,                       // aka offmax
     pub off_min_performance: f32,                       // aka offmin
+    // Generations since the best raw score last improved.
+    #[cfg_attr(feature = "serde", serde(default))]
+    generations_since_improvement: usize,
 
     // Call generation_statistics(1) instead.
     // init_avg_score: f32,                // aka aveInit
@@ -53,9 +73,17 @@ pub struct GAStatistics<T: GAIndividual>
 
 }
 
+impl<T: GAIndividual> Default for GAStatistics<T>
+{
+    fn default() -> GAStatistics<T>
+    {
+        GAStatistics::new()
+    }
+}
+
 impl<T: GAIndividual> GAStatistics<T>
 {
-    fn new() -> GAStatistics<T>
+    pub fn new() -> GAStatistics<T>
     {
         GAStatistics
         {
@@ -69,6 +97,11 @@ impl<T: GAIndividual> GAStatistics<T>
             cur_generation: 0,
             record_frequency: 1,
             record_diversity: false,
+            record_timing: false,
+
+            last_time: None,
+            generation_times: Vec::new(),
+            timed_evaluations: 0,
 
             alltime_best_pop: None,
             alltime_max_score: 0.0,
@@ -76,6 +109,7 @@ impl<T: GAIndividual> GAStatistics<T>
             on_performance: 0.0,
             off_max_performance: 0.0,
             off_min_performance: 0.0,
+            generations_since_improvement: 0,
 
             //init_avg_score: 0.0,
             //init_max_score: 0.0,
@@ -100,7 +134,20 @@ impl<T: GAIndividual> GAStatistics<T>
         }
     }
 
-    fn update(&mut self, pop: &mut GAPopulation<T>) where T: Clone + PartialEq
+    /// Record a new generation. `pop` must be sorted.
+    ///
+    /// Counts every individual of `pop` as evaluated, see
+    /// `update_with_evaluations` when only some of them were.
+    pub fn update(&mut self, pop: &mut GAPopulation<T>) where T: Clone + PartialEq
+    {
+        let evaluations = pop.size();
+        self.update_with_evaluations(pop, evaluations);
+    }
+
+    /// Record a new generation, in which `evaluations` individuals were
+    /// evaluated (e.g. what `GAPopulation::evaluate` returned). `pop` must be
+    /// sorted.
+    pub fn update_with_evaluations(&mut self, pop: &mut GAPopulation<T>, evaluations: usize) where T: Clone + PartialEq
     {
         match pop.statistics()
         {
@@ -115,6 +162,21 @@ impl<T: GAIndividual> GAStatistics<T>
 
                 // TODO: Flush scores.
 
+                let improved = match (self.best_raw(), self.order())
+                {
+                    (None, _) => true,
+                    (Some(best), GAPopulationSortOrder::HighIsBest) => stats.raw_max > best,
+                    (Some(best), GAPopulationSortOrder::LowIsBest) => stats.raw_min < best
+                };
+                if improved
+                {
+                    self.generations_since_improvement = 0;
+                }
+                else
+                {
+                    self.generations_since_improvement += 1;
+                }
+
                 self.alltime_max_score = self.alltime_max_score.max(stats.raw_max);
                 self.alltime_min_score = self.alltime_min_score.min(stats.raw_min);
                 self.on_performance = (self.on_performance * (self.cur_generation-1) as f32 + stats.raw_avg) / self.cur_generation as f32;
@@ -129,17 +191,25 @@ impl<T: GAIndividual> GAStatistics<T>
                 
                 // Archive this generation's statistics.
                 self.hist_stats.push(stats);
+
+                self.num_ind_evaluations += evaluations;
+                self.num_pop_evaluations += 1;
+                self.record_time(evaluations);
             }
         }
     }
 
-    fn best(&self) -> Option<GAPopulation<T>> where T: Clone
+    /// The all-time best population.
+    pub fn best(&self) -> Option<GAPopulation<T>> where T: Clone
     {
         self.alltime_best_pop.clone()
     }
 
-    // Set generation #1. Or reset to new generation #1.
-    fn set_best(&mut self, mut pop: GAPopulation<T>)
+    /// Set generation #1. Or reset to new generation #1.
+    ///
+    /// `pop` becomes the all-time best population, later generations'
+    /// best individuals replace its worst ones.
+    pub fn set_best(&mut self, mut pop: GAPopulation<T>)
     {
         match pop.statistics()
         {
@@ -150,14 +220,25 @@ impl<T: GAIndividual> GAStatistics<T>
             Some(stats) =>
             {
                 self.cur_generation = 1;
+                self.generations_since_improvement = 0;
                 self.alltime_max_score = self.alltime_max_score.max(stats.raw_max);
                 self.alltime_min_score = self.alltime_min_score.min(stats.raw_min);
                 self.on_performance = (self.on_performance * (self.cur_generation-1) as f32 + stats.raw_avg) / self.cur_generation as f32;
                 self.off_max_performance = (self.off_max_performance * (self.cur_generation-1) as f32 + stats.raw_max) / self.cur_generation as f32;
                 self.off_min_performance = (self.off_min_performance * (self.cur_generation-1) as f32 + stats.raw_min) / self.cur_generation as f32;
 
+                self.num_ind_evaluations += pop.size();
+                self.num_pop_evaluations += 1;
+
                 self.alltime_best_pop = Some(pop);
                 self.hist_stats.push(stats);
+
+                if self.record_timing
+                {
+                    self.last_time = Some(Instant::now());
+                    self.generation_times.clear();
+                    self.timed_evaluations = 0;
+                }
             }
         }
     }
@@ -181,7 +262,7 @@ impl<T: GAIndividual> GAStatistics<T>
 
                 if best_pop_size == 1
                 {
-                    let mut best_pop_best_ind = best_pop.best_by_raw_score_mut();
+                    let best_pop_best_ind = best_pop.best_by_raw_score_mut();
                     let pop_best_ind = pop.best_by_raw_score();
 
                     if (order == GAPopulationSortOrder::LowIsBest
@@ -280,8 +361,8 @@ impl<T: GAIndividual> GAStatistics<T>
         }
     }
 
-    // Get the statistics of the nth generation (#1 is the first one).
-    fn generation_statistics(&mut self, nth_generation: usize) -> Option<GAPopulationStats>
+    /// Get the statistics of the nth generation (#1 is the first one).
+    pub fn generation_statistics(&self, nth_generation: usize) -> Option<GAPopulationStats>
     {
         if nth_generation > 0 && nth_generation <= self.hist_stats.len()
         {
@@ -293,8 +374,8 @@ impl<T: GAIndividual> GAStatistics<T>
         }
     }
 
-    // Get the statistics of the alltime-best individuals.
-    fn alltime_best_statistics(&mut self) -> Option<GAPopulationStats>
+    /// Get the statistics of the alltime-best individuals.
+    pub fn alltime_best_statistics(&mut self) -> Option<GAPopulationStats>
     {
         match self.alltime_best_pop
         {
@@ -302,6 +383,178 @@ impl<T: GAIndividual> GAStatistics<T>
             None => None
         }
     }
+
+    /// Record the wall-clock time of each generation.
+    ///
+    /// Off by default to avoid the overhead. Takes effect from the next call to
+    /// `set_best` (or `update`, if `set_best` was called already).
+    pub fn set_record_timing(&mut self, record_timing: bool)
+    {
+        self.record_timing = record_timing;
+    }
+
+    fn record_time(&mut self, evaluations: usize)
+    {
+        if !self.record_timing
+        {
+            return;
+        }
+
+        let now = Instant::now();
+        if let Some(last_time) = self.last_time
+        {
+            self.generation_times.push(now.duration_since(last_time));
+            self.timed_evaluations += evaluations;
+        }
+        self.last_time = Some(now);
+    }
+
+    /// Wall-clock time of each generation recorded after `set_best` (empty
+    /// unless timing is recorded).
+    pub fn generation_times(&self) -> &[Duration]
+    {
+        &self.generation_times
+    }
+
+    /// Individual evaluations per second, over the recorded wall-clock time.
+    /// Only the evaluations of the timed generations count: the initial
+    /// population, evaluated before `set_best`, isn't timed.
+    ///
+    /// 0 when timing isn't recorded or no time has elapsed yet.
+    pub fn evaluations_per_second(&self) -> f32
+    {
+        let elapsed = self.generation_times.iter().fold(Duration::new(0, 0), |sum, t| sum + *t);
+        let secs = elapsed.as_secs() as f32 + elapsed.subsec_nanos() as f32 * 1e-9;
+
+        if secs > 0.0
+        {
+            self.timed_evaluations as f32 / secs
+        }
+        else
+        {
+            0.0
+        }
+    }
+
+    // Sort order of the recorded populations (taken from the all-time best
+    // population). HighIsBest when nothing has been recorded yet.
+    fn order(&self) -> GAPopulationSortOrder
+    {
+        match self.alltime_best_pop
+        {
+            Some(ref best_pop) => best_pop.order(),
+            None => GAPopulationSortOrder::HighIsBest
+        }
+    }
+
+    // Best raw score of all the recorded generations, if any.
+    fn best_raw(&self) -> Option<f32>
+    {
+        let order = self.order();
+        self.best_raw_series().into_iter().fold(None, |best: Option<f32>, raw|
+        {
+            match (best, order)
+            {
+                (None, _) => Some(raw),
+                (Some(b), GAPopulationSortOrder::HighIsBest) => Some(b.max(raw)),
+                (Some(b), GAPopulationSortOrder::LowIsBest) => Some(b.min(raw))
+            }
+        })
+    }
+
+    /// Number of generations recorded since the best raw score last
+    /// improved (on the best of all the previous generations), e.g. to
+    /// report plateaus. 0 right after an improvement and after `set_best`.
+    pub fn generations_since_improvement(&self) -> usize
+    {
+        self.generations_since_improvement
+    }
+
+    /// Best raw score of each recorded generation, in generation order.
+    pub fn best_raw_series(&self) -> Vec<f32>
+    {
+        let order = self.order();
+        self.hist_stats.iter().map(|stats|
+        {
+            match order
+            {
+                GAPopulationSortOrder::HighIsBest => stats.raw_max,
+                GAPopulationSortOrder::LowIsBest => stats.raw_min
+            }
+        }).collect()
+    }
+
+    /// Early stopping recommendation: true when the best raw score of the
+    /// last `patience` recorded generations doesn't beat the best score of
+    /// the generations before them by at least `min_delta`.
+    ///
+    /// False until more than `patience` generations have been recorded.
+    pub fn should_stop(&self, patience: usize, min_delta: f32) -> bool
+    {
+        let series = self.best_raw_series();
+        if series.len() <= patience
+        {
+            return false;
+        }
+
+        let (before, recent) = series.split_at(series.len() - patience);
+        let improvement = match self.order()
+        {
+            GAPopulationSortOrder::HighIsBest =>
+                recent.iter().cloned().fold(f32::NEG_INFINITY, f32::max) - before.iter().cloned().fold(f32::NEG_INFINITY, f32::max),
+            GAPopulationSortOrder::LowIsBest =>
+                before.iter().cloned().fold(f32::INFINITY, f32::min) - recent.iter().cloned().fold(f32::INFINITY, f32::min)
+        };
+
+        improvement < min_delta
+    }
+
+    /// Convergence by collapse of the population: true when the raw score
+    /// variance of the last recorded generation is below `var_threshold`.
+    ///
+    /// Unlike `should_stop`, which looks for a plateau of the best score,
+    /// this looks at the spread of the whole population. False when no
+    /// generation has been recorded.
+    pub fn has_converged(&self, var_threshold: f32) -> bool
+    {
+        match self.hist_stats.last()
+        {
+            Some(stats) => stats.raw_var < var_threshold,
+            None => false
+        }
+    }
+
+    /// One line summary of the run so far: current generation, all-time best
+    /// raw score, and the last generation's average raw score, diversity
+    /// (see `GAPopulationStats::score_diversity`) and number of evaluations.
+    pub fn summary_string(&self) -> String
+    {
+        match (self.best_raw(), self.hist_stats.last())
+        {
+            (Some(best), Some(stats)) =>
+                format!("generation {} | best {} | avg {} | diversity {} | evaluations {}",
+                        self.cur_generation, best, stats.raw_avg, stats.score_diversity(), self.num_ind_evaluations),
+            _ =>
+                format!("generation {} | no statistics recorded", self.cur_generation)
+        }
+    }
+
+    /// Best fitness score of each recorded generation, in generation order.
+    ///
+    /// Useful to plot the convergence of the scaled fitness separately from
+    /// the raw score.
+    pub fn best_fitness_series(&self) -> Vec<f32>
+    {
+        let order = self.order();
+        self.hist_stats.iter().map(|stats|
+        {
+            match order
+            {
+                GAPopulationSortOrder::HighIsBest => stats.fitness_max,
+                GAPopulationSortOrder::LowIsBest => stats.fitness_min
+            }
+        }).collect()
+    }
 }
 
 ////////////////////////////////////////
@@ -332,14 +585,7 @@ mod test
         let expected_var_1 = raw_scores_1.iter().fold(0.0, |var, rs| var + (rs - expected_avg_1).powi(2)) / (raw_scores_1.len()-1) as f32;
         let expected_std_dev_1 = expected_var_1.sqrt();
 
-        let mut inds_1: Vec<GATestIndividual> = Vec::new();
-        for rs in raw_scores_1.iter().cloned()
-        {
-            inds_1.push(GATestIndividual::new(rs));
-        }
-        let mut pop_1 = GAPopulation::new(inds_1, GAPopulationSortOrder::LowIsBest);
-        pop_1.sort();
-        pop_1.statistics();
+        let mut pop_1 = population_from_raw_scores(&raw_scores_1, GAPopulationSortOrder::LowIsBest);
 
         // Generation 2.
 
@@ -351,14 +597,7 @@ mod test
         let expected_var_2 = raw_scores_2.iter().fold(0.0, |var, rs| var + (rs - expected_avg_2).powi(2)) / (raw_scores_2.len()-1) as f32;
         let expected_std_dev_2 = expected_var_2.sqrt();
 
-        let mut inds_2: Vec<GATestIndividual> = Vec::new();
-        for rs in raw_scores_2.iter().cloned()
-        {
-            inds_2.push(GATestIndividual::new(rs));
-        }
-        let mut pop_2 = GAPopulation::new(inds_2, GAPopulationSortOrder::LowIsBest);
-        pop_2.sort();
-        pop_2.statistics();
+        let mut pop_2 = population_from_raw_scores(&raw_scores_2, GAPopulationSortOrder::LowIsBest);
 
         // Statistics after generation 1.
 
@@ -370,6 +609,7 @@ mod test
         assert_eq!(stats.alltime_max_score == expected_max_1, true);
         assert_eq!(stats.alltime_min_score == expected_min_1, true);
         assert_eq!(gen1_stats == pop1_stats, true);
+        assert_eq!(gen1_stats.approx_eq(&pop1_stats, 0.00001), true);
 
         // Statistics after generation 2.
 
@@ -380,6 +620,7 @@ mod test
         assert_eq!(stats.alltime_max_score == expected_max_2, true);
         assert_eq!(stats.alltime_min_score == expected_min_1, true);
         assert_eq!(gen2_stats == pop2_stats, true);
+        assert_eq!(gen2_stats.approx_eq(&pop2_stats, 0.00001), true);
 
         ga_test_teardown();
     }
@@ -519,14 +760,7 @@ mod test
             // Odd scores.
             let raw_scores_2: Vec<f32> = vec![-9.0, -7.0, -5.0, -3.0, -1.0, 1.0, 3.0, 5.0, 7.0, 9.0, 11.0];
 
-            let mut inds_1: Vec<GATestIndividual> = Vec::new();
-            for rs in raw_scores_1.iter().cloned()
-            {
-                inds_1.push(GATestIndividual::new(rs));
-            }
-            let mut pop_1 = GAPopulation::new(inds_1, GAPopulationSortOrder::HighIsBest);
-            pop_1.sort();
-            pop_1.statistics();
+            let mut pop_1 = population_from_raw_scores(&raw_scores_1, GAPopulationSortOrder::HighIsBest);
 
             let mut inds_2: Vec<GATestIndividual> = Vec::new();
             for rs in raw_scores_2.iter().cloned()
@@ -541,18 +775,11 @@ mod test
             stats.set_best(pop_1.clone());
             stats.update_best(&pop_2);
 
-            let mut best_pop = stats.best().unwrap();
+            let best_pop = stats.best().unwrap();
 
             let best_raw_scores: Vec<f32> = vec![11.0, 10.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0];
 
-            let mut best_inds: Vec<GATestIndividual> = Vec::new();
-            for rs in best_raw_scores.iter().cloned()
-            {
-                best_inds.push(GATestIndividual::new(rs));
-            }
-            let mut expected_best_pop = GAPopulation::new(best_inds, GAPopulationSortOrder::HighIsBest);
-            expected_best_pop.sort();
-            expected_best_pop.statistics();
+            let expected_best_pop = population_from_raw_scores(&best_raw_scores, GAPopulationSortOrder::HighIsBest);
 
             assert_eq!(best_pop == expected_best_pop, true);
         }
@@ -567,14 +794,7 @@ mod test
             // Raw scores.
             let raw_scores_2: Vec<f32> = vec![-9.0, -7.0, -5.0, -3.0, -1.0, 1.0, 3.0, 5.0, 7.0, 9.0, 11.0];
 
-            let mut inds_1: Vec<GATestIndividual> = Vec::new();
-            for rs in raw_scores_1.iter().cloned()
-            {
-                inds_1.push(GATestIndividual::new(rs));
-            }
-            let mut pop_1 = GAPopulation::new(inds_1, GAPopulationSortOrder::LowIsBest);
-            pop_1.sort();
-            pop_1.statistics();
+            let mut pop_1 = population_from_raw_scores(&raw_scores_1, GAPopulationSortOrder::LowIsBest);
 
             let mut inds_2: Vec<GATestIndividual> = Vec::new();
             for rs in raw_scores_2.iter().cloned()
@@ -589,22 +809,252 @@ mod test
             stats.set_best(pop_1.clone());
             stats.update_best(&pop_2);
 
-            let mut best_pop = stats.best().unwrap();
+            let best_pop = stats.best().unwrap();
 
             let best_raw_scores: Vec<f32> = vec![-10.0, -9.0, -8.0, -7.0, -6.0, -5.0, -4.0, -3.0, -2.0, -1.0, 0.1];
 
-            let mut best_inds: Vec<GATestIndividual> = Vec::new();
-            for rs in best_raw_scores.iter().cloned()
+            let expected_best_pop = population_from_raw_scores(&best_raw_scores, GAPopulationSortOrder::LowIsBest);
+
+            assert_eq!(best_pop == expected_best_pop, true);
+        }
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_best_score_series()
+    {
+        ga_test_setup("ga_statistics::test_best_score_series");
+
+        let mut pops: Vec<GAPopulation<GATestIndividual>> = Vec::new();
+        for g in 1..5
+        {
+            let inds = vec![GATestIndividual::new(g as f32), GATestIndividual::new(2.0 * g as f32)];
+            let mut pop = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+            pop.sort();
+            pop.statistics();
+            pops.push(pop);
+        }
+
+        let mut stats = GAStatistics::<GATestIndividual>::new();
+        stats.set_best(pops[0].clone());
+        for pop in pops.iter_mut().skip(1)
+        {
+            stats.update(pop);
+        }
+
+        let fitness_series = stats.best_fitness_series();
+        assert_eq!(fitness_series.len(), stats.hist_stats.len());
+        assert_eq!(fitness_series.len(), 4);
+        // fitness = 1/raw
+        assert_eq!(fitness_series, vec![1.0, 0.5, 1.0/3.0, 0.25]);
+
+        let raw_series = stats.best_raw_series();
+        assert_eq!(raw_series, vec![2.0, 4.0, 6.0, 8.0]);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    fn test_evaluations_per_second()
+    {
+        ga_test_setup("ga_statistics::test_evaluations_per_second");
+
+        let mut fact = GATestFactory::new(0.0);
+        let rng_ctx = &mut GARandomCtx::from_seed([1,2,3,4], "ga_statistics::test_evaluations_per_second".to_string());
+
+        let mut pop = fact.random_population(100, GAPopulationSortOrder::HighIsBest, rng_ctx);
+        pop.sort();
+
+        let mut stats = GAStatistics::<GATestIndividual>::new();
+        stats.set_record_timing(true);
+        stats.set_best(pop.clone());
+
+        for _ in 0..5
+        {
+            let mut next_pop = fact.random_population(100, GAPopulationSortOrder::HighIsBest, rng_ctx);
+            next_pop.sort();
+            stats.update(&mut next_pop);
+        }
+
+        // Only some individuals were evaluated (e.g. elites survived).
+        let mut next_pop = fact.random_population(100, GAPopulationSortOrder::HighIsBest, rng_ctx);
+        next_pop.sort();
+        stats.update_with_evaluations(&mut next_pop, 30);
+
+        assert_eq!(stats.num_ind_evaluations, 630);
+        assert_eq!(stats.generation_times().len(), 6);
+        assert_eq!(stats.timed_evaluations, 530);
+        assert!(stats.evaluations_per_second() > 0.0);
+
+        // Without timing.
+        let mut untimed_stats = GAStatistics::<GATestIndividual>::new();
+        untimed_stats.set_best(pop);
+        assert_eq!(untimed_stats.generation_times().len(), 0);
+        assert_eq!(untimed_stats.evaluations_per_second(), 0.0);
+
+        ga_test_teardown();
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_statistics_serde_round_trip()
+    {
+        ga_test_setup("ga_statistics::test_statistics_serde_round_trip");
+        use serde_json;
+
+        let mut stats = GAStatistics::<GATestIndividual>::new();
+        stats.set_record_timing(true);
+        for g in 1..5
+        {
+            let inds = vec![GATestIndividual::new(g as f32), GATestIndividual::new(2.0 * g as f32)];
+            let mut pop = GAPopulation::new(inds, GAPopulationSortOrder::HighIsBest);
+            pop.sort();
+            pop.statistics();
+            if g == 1 { stats.set_best(pop); } else { stats.update(&mut pop); }
+        }
+
+        let json = serde_json::to_string(&stats).unwrap();
+        let loaded : GAStatistics<GATestIndividual> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.num_ind_evaluations, stats.num_ind_evaluations);
+        assert_eq!(loaded.num_pop_evaluations, stats.num_pop_evaluations);
+        assert_eq!(loaded.cur_generation, stats.cur_generation);
+        assert_eq!(loaded.record_timing, stats.record_timing);
+        assert_eq!(loaded.generation_times, stats.generation_times);
+        assert_eq!(loaded.timed_evaluations, stats.timed_evaluations);
+        assert_eq!(loaded.alltime_max_score, stats.alltime_max_score);
+        assert_eq!(loaded.alltime_min_score, stats.alltime_min_score);
+        assert_eq!(loaded.on_performance, stats.on_performance);
+        assert_eq!(loaded.hist_stats.len(), stats.hist_stats.len());
+        assert!(loaded.hist_stats.iter().zip(stats.hist_stats.iter()).all(|(l, s)| l == s));
+        assert_eq!(loaded.alltime_best_pop.as_ref().unwrap() == stats.alltime_best_pop.as_ref().unwrap(), true);
+
+        // Nothing lost along the way.
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn test_summary_string()
+    {
+        ga_test_setup("ga_statistics::test_summary_string");
+
+        let mut stats = GAStatistics::<GATestIndividual>::new();
+        assert_eq!(stats.summary_string(), "generation 0 | no statistics recorded");
+
+        let inds = vec![GATestIndividual::new(1.0), GATestIndividual::new(3.0)];
+        let mut pop_1 = GAPopulation::new(inds, GAPopulationSortOrder::LowIsBest);
+        pop_1.sort();
+        stats.set_best(pop_1);
+
+        let inds = vec![GATestIndividual::new(2.0), GATestIndividual::new(4.0)];
+        let mut pop_2 = GAPopulation::new(inds, GAPopulationSortOrder::LowIsBest);
+        pop_2.sort();
+        stats.update(&mut pop_2);
+
+        let summary = stats.summary_string();
+        debug!("{}", summary);
+        assert!(summary.contains("generation 2"));
+        // All-time best, not the last generation's.
+        assert!(summary.contains("best 1 "));
+        assert!(summary.contains("avg 3 "));
+        assert!(summary.contains("evaluations 4"));
+
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn test_should_stop()
+    {
+        ga_test_setup("ga_statistics::test_should_stop");
+
+        for &(order, sign) in [(GAPopulationSortOrder::HighIsBest, 1.0), (GAPopulationSortOrder::LowIsBest, -1.0)].iter()
+        {
+            let mut stats = GAStatistics::<GATestIndividual>::new();
+            // Best scores: 1, 2, 3, then a plateau.
+            for (i, best) in [1.0, 2.0, 3.0, 3.0, 3.0, 3.0].iter().enumerate()
             {
-                best_inds.push(GATestIndividual::new(rs));
+                let mut pop = GAPopulation::new(vec![GATestIndividual::new(sign * best),
+                                                     GATestIndividual::new(sign * (best - 10.0))],
+                                                order);
+                pop.sort();
+                if i == 0
+                {
+                    stats.set_best(pop);
+                }
+                else
+                {
+                    stats.update(&mut pop);
+                }
+
+                // 3 generations without improvement.
+                assert_eq!(stats.should_stop(3, 0.5), i >= 5);
             }
-            let mut expected_best_pop = GAPopulation::new(best_inds, GAPopulationSortOrder::LowIsBest);
-            expected_best_pop.sort();
-            expected_best_pop.statistics();
 
-            assert_eq!(best_pop == expected_best_pop, true);
+            // Improved within the last 4 generations.
+            assert!(!stats.should_stop(4, 0.5));
+            assert!(stats.should_stop(4, 1.5));
+            // Not enough history.
+            assert!(!stats.should_stop(6, 0.5));
         }
 
         ga_test_teardown();
     }
+
+
+    #[test]
+    fn test_has_converged()
+    {
+        ga_test_setup("ga_statistics::test_has_converged");
+
+        let mut stats = GAStatistics::<GATestIndividual>::new();
+        assert!(!stats.has_converged(1.0));
+
+        // Spread out, then collapsed around 5.
+        let mut pop = GAPopulation::new(vec![GATestIndividual::new(1.0), GATestIndividual::new(10.0)], GAPopulationSortOrder::HighIsBest);
+        pop.sort();
+        stats.set_best(pop);
+        assert!(!stats.has_converged(1.0));
+
+        let mut pop = GAPopulation::new(vec![GATestIndividual::new(5.0), GATestIndividual::new(5.01), GATestIndividual::new(4.99)],
+                                        GAPopulationSortOrder::HighIsBest);
+        pop.sort();
+        stats.update(&mut pop);
+        assert!(stats.has_converged(1.0));
+        assert!(!stats.has_converged(1e-6));
+
+        ga_test_teardown();
+    }
+
+
+    #[test]
+    fn test_generations_since_improvement()
+    {
+        ga_test_setup("ga_statistics::test_generations_since_improvement");
+        for &(order, sign) in [(GAPopulationSortOrder::HighIsBest, 1.0), (GAPopulationSortOrder::LowIsBest, -1.0)].iter()
+        {
+            let mut stats = GAStatistics::<GATestIndividual>::new();
+            stats.set_best(population_from_raw_scores(&[sign * 1.0, sign * 2.0], order));
+            assert_eq!(stats.generations_since_improvement(), 0);
+
+            // Improving generation.
+            stats.update(&mut population_from_raw_scores(&[sign * 1.0, sign * 3.0], order));
+            assert_eq!(stats.generations_since_improvement(), 0);
+
+            // Flat generations: matching the best isn't improving it.
+            for (i, raws) in [[1.0, 3.0], [2.0, 2.5], [3.0, 3.0]].iter().enumerate()
+            {
+                stats.update(&mut population_from_raw_scores(&[sign * raws[0], sign * raws[1]], order));
+                assert_eq!(stats.generations_since_improvement(), i + 1);
+            }
+
+            stats.update(&mut population_from_raw_scores(&[sign * 4.0], order));
+            assert_eq!(stats.generations_since_improvement(), 0);
+        }
+        ga_test_teardown();
+    }
 }
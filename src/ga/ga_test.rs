@@ -48,6 +48,7 @@ pub fn ga_test_teardown(){}
 /// GATestIndividual
 /// Implements the GAIndividual Trait with only no-ops
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GATestIndividual
 {
     raw: f32,
@@ -73,6 +74,21 @@ impl GAIndividual for GATestIndividual
     fn raw(&self) -> f32 { self.raw }
     fn set_raw(&mut self, raw: f32) { self.raw = raw; }
 }
+impl GADiversity for GATestIndividual
+{
+    fn distance(&self, other: &GATestIndividual) -> f32 { (self.raw - other.raw).abs() }
+}
+
+/// Population of `GATestIndividual`s with the given raw scores, sorted and
+/// with its statistics computed.
+pub fn population_from_raw_scores(scores: &[f32], order: GAPopulationSortOrder) -> GAPopulation<GATestIndividual>
+{
+    let inds = scores.iter().map(|rs| GATestIndividual::new(*rs)).collect();
+    let mut pop = GAPopulation::new(inds, order);
+    pop.sort();
+    pop.statistics();
+    pop
+}
 
 pub struct GATestFactory
 {
@@ -132,3 +148,32 @@ impl GAFactory<GATestIndividual> for GATestFactory
         GAPopulation::new(inds, pop.order())
     }
 }
+
+////////////////////////////////////////
+// Tests
+#[cfg(test)]
+mod test
+{
+    use super::*;
+
+    #[test]
+    fn test_population_from_raw_scores()
+    {
+        ga_test_setup("ga_test::test_population_from_raw_scores");
+        let scores = [3.0, -1.0, 7.5, 2.0];
+
+        let mut pop = population_from_raw_scores(&scores, GAPopulationSortOrder::HighIsBest);
+        assert_eq!(pop.size(), 4);
+        assert_eq!(pop.best_by_raw_score().raw(), 7.5);
+        assert_eq!(pop.statistics().unwrap().raw_max, 7.5);
+
+        let pop = population_from_raw_scores(&scores, GAPopulationSortOrder::LowIsBest);
+        assert_eq!(pop.size(), 4);
+        assert_eq!(pop.best_by_raw_score().raw(), -1.0);
+
+        let mut pop = population_from_raw_scores(&[], GAPopulationSortOrder::HighIsBest);
+        assert_eq!(pop.size(), 0);
+        assert!(pop.statistics().is_none());
+        ga_test_teardown();
+    }
+}
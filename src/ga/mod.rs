@@ -1,7 +1,14 @@
 // Copyright 2016 Revolution Solid & Contributors.
 // author(s): sysnett
 // rust-monster is licensed under a MIT License.
+pub mod ga_alps;
+pub mod ga_coevolution;
 pub mod ga_core;
+#[macro_use]
+pub mod ga_crossover;
+pub mod ga_diploid;
+pub mod ga_individuals;
+pub mod ga_mutation;
 pub mod ga_population;
 pub mod ga_random;
 pub mod ga_scaling;
@@ -10,6 +10,13 @@ extern crate bitflags;
 extern crate log;
 
 extern crate rand;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 
 // Published Modules
 pub mod ga;
@@ -15,8 +15,9 @@ mod tests
     use rust_monster::ga::ga_simple::*;
     use rust_monster::ga::ga_population::*;
     use rust_monster::ga::ga_core::*;
+    use rust_monster::ga::ga_crossover::{GACrossover, OrderCrossover};
+    use rust_monster::ga::ga_mutation::{GAMutation, SwapMutation};
 
-    use std::cmp::min;
     use std::any::Any;
     use std::f64;
 
@@ -42,7 +43,7 @@ mod tests
         }
     }
 
-    #[derive(Clone)]
+    #[derive(Clone, PartialEq)]
     struct TSPIndividual
     {
         raw: f32,
@@ -72,50 +73,13 @@ mod tests
     }
     impl GAIndividual for TSPIndividual
     {
-        // Crossing over a permutation isn't as simple as one might think
-        // algorithm inspired in: http://www.permutationcity.co.uk/projects/mutants/tsp.html
         fn crossover(&self, other: &TSPIndividual, ctx: &mut Any) -> Box<TSPIndividual>
         {
             match ctx.downcast_mut::<GARandomCtx>()
             {
                 Some(rng_ctx) =>
                 {
-                    let to_pick = min(self.inxes.len(), 3);
-                    let mut new_inxes = vec![];
-        
-                    // Copy the first parent
-                    for i in 0..self.inxes.len()
-                    {
-                        new_inxes.push(self.inxes[i]);
-                    }
-        
-                    let mut picked = vec![]; //This are indexes
-                    for _ in 0..to_pick
-                    {
-                        picked.push(rng_ctx.gen_range(0, self.inxes.len()));
-                    }
-        
-                    let mut places: [usize; 3] = [0, 0, 0];
-                    for pi in 0..picked.len()
-                    {
-                        for oi in 0..other.inxes.len()
-                        {
-                            if self.inxes[picked[pi]] == other.inxes[oi]
-                            {
-                                places[pi] = oi;
-                                break;
-                            }
-                        }
-                    }
-        
-                    for pi in 0..picked.len()
-                    {
-                        let temp = new_inxes[picked[pi]];
-                        new_inxes[picked[pi]] = new_inxes[places[pi]];
-                        new_inxes[places[pi]] = temp;
-                    }
-        
-                    Box::new(TSPIndividual::new_from_inxes(new_inxes))
+                    Box::new(TSPIndividual::new_from_inxes(OrderCrossover.cross(&self.inxes, &other.inxes, rng_ctx)))
                 },
                 None =>
                 {
@@ -130,20 +94,7 @@ mod tests
             {
                 Some(rng) =>
                 {
-                    if rng.test_value(probability)
-                    {
-                        let p1 = rng.gen_range(0, self.inxes.len());
-                        let mut p2 = p1;
-        
-                        while p1 == p2
-                        {
-                            p2 = rng.gen_range(0, self.inxes.len());
-                        }
-        
-                        let tmp = self.inxes[p1];
-                        self.inxes[p1] = self.inxes[p2];
-                        self.inxes[p2] = tmp;
-                    }
+                    SwapMutation.mutate(&mut self.inxes, probability, rng);
                 },
                 None =>
                 {
@@ -0,0 +1,80 @@
+// Copyright 2016 Revolution Solid & Contributors.
+// author(s): sysnett
+// rust-monster is licensed under a MIT License.
+
+// Per-generation logging flags. Lives in its own test binary, since it
+// installs a capturing logger (the logger is process wide).
+extern crate rust_monster;
+extern crate log;
+
+use rust_monster::ga::ga_core::*;
+use rust_monster::ga::ga_population::*;
+use rust_monster::ga::ga_simple::*;
+use rust_monster::ga::ga_test::*;
+
+use log::{Log, LogLevelFilter, LogMetadata, LogRecord};
+use std::sync::Mutex;
+
+static CAPTURED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct CaptureLogger;
+impl Log for CaptureLogger
+{
+    fn enabled(&self, _: &LogMetadata) -> bool { true }
+
+    fn log(&self, record: &LogRecord)
+    {
+        CAPTURED.lock().unwrap().push(format!("{}", record.args()));
+    }
+}
+
+fn run_ga(flags: GAFlags) -> Vec<String>
+{
+    CAPTURED.lock().unwrap().clear();
+
+    let mut factory = GATestFactory::new(GA_TEST_FITNESS_VAL);
+    let mut ga : SimpleGeneticAlgorithm<GATestIndividual> =
+                 SimpleGeneticAlgorithm::new(SimpleGeneticAlgorithmCfg {
+                                               d_seed : [1; 4],
+                                               flags : flags,
+                                               max_generations: 3,
+                                               population_size: 10,
+                                               population_sort_order: GAPopulationSortOrder::HighIsBest,
+                                               ..Default::default()
+                                             },
+                                             Some(&mut factory),
+                                             None);
+    ga.initialize();
+    while !ga.done()
+    {
+        ga.step();
+    }
+
+    let lines = CAPTURED.lock().unwrap().clone();
+    lines
+}
+
+#[test]
+fn generation_logging_flags()
+{
+    log::set_logger(|max_log_level|
+    {
+        max_log_level.set(LogLevelFilter::Debug);
+        Box::new(CaptureLogger)
+    }).unwrap();
+
+    let is_summary = |l: &String| l.starts_with("Generation ") && l.contains("best raw");
+    let is_full = |l: &String| l.ends_with("statistics:");
+
+    let quiet = run_ga(GAFlags::default());
+    assert!(!quiet.iter().any(&is_summary));
+    assert!(!quiet.iter().any(&is_full));
+
+    let summary = run_ga(LOG_GENERATION_SUMMARY);
+    assert_eq!(summary.iter().filter(|l| is_summary(l)).count(), 3);
+    assert!(!summary.iter().any(&is_full));
+
+    let full = run_ga(LOG_FULL_STATS);
+    assert!(!full.iter().any(&is_summary));
+    assert_eq!(full.iter().filter(|l| is_full(l)).count(), 3);
+}